@@ -0,0 +1,231 @@
+use std::fmt::Display;
+
+use crate::{nearly_zero, Matrix, Point, Ray};
+
+// Axis-aligned bounding box, bounds may be infinite
+#[derive(Clone, Debug)]
+pub struct Aabb {
+    pub min: Point,
+    pub max: Point,
+}
+
+impl Display for Aabb {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[{} {}]", self.min, self.max)
+    }
+}
+
+impl Aabb {
+    pub fn new(min: Point, max: Point) -> Aabb {
+        Aabb { min, max }
+    }
+
+    pub fn empty() -> Aabb {
+        Aabb {
+            min: Point::new(f64::INFINITY, f64::INFINITY, f64::INFINITY),
+            max: Point::new(f64::NEG_INFINITY, f64::NEG_INFINITY, f64::NEG_INFINITY),
+        }
+    }
+
+    pub fn infinite() -> Aabb {
+        Aabb {
+            min: Point::new(f64::NEG_INFINITY, f64::NEG_INFINITY, f64::NEG_INFINITY),
+            max: Point::new(f64::INFINITY, f64::INFINITY, f64::INFINITY),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.min.x > self.max.x || self.min.y > self.max.y || self.min.z > self.max.z
+    }
+
+    pub fn is_finite(&self) -> bool {
+        [
+            self.min.x, self.min.y, self.min.z, self.max.x, self.max.y, self.max.z,
+        ]
+        .iter()
+        .all(|k| k.is_finite())
+    }
+
+    pub fn contains(&self, p: &Point) -> bool {
+        (self.min.x..=self.max.x).contains(&p.x)
+            && (self.min.y..=self.max.y).contains(&p.y)
+            && (self.min.z..=self.max.z).contains(&p.z)
+    }
+
+    pub fn union(&self, b: &Aabb) -> Aabb {
+        Aabb {
+            min: Point::new(
+                self.min.x.min(b.min.x),
+                self.min.y.min(b.min.y),
+                self.min.z.min(b.min.z),
+            ),
+            max: Point::new(
+                self.max.x.max(b.max.x),
+                self.max.y.max(b.max.y),
+                self.max.z.max(b.max.z),
+            ),
+        }
+    }
+
+    pub fn intersection(&self, b: &Aabb) -> Aabb {
+        Aabb {
+            min: Point::new(
+                self.min.x.max(b.min.x),
+                self.min.y.max(b.min.y),
+                self.min.z.max(b.min.z),
+            ),
+            max: Point::new(
+                self.max.x.min(b.max.x),
+                self.max.y.min(b.max.y),
+                self.max.z.min(b.max.z),
+            ),
+        }
+    }
+
+    pub fn extend(&mut self, p: &Point) {
+        self.min = Point::new(
+            self.min.x.min(p.x),
+            self.min.y.min(p.y),
+            self.min.z.min(p.z),
+        );
+        self.max = Point::new(
+            self.max.x.max(p.x),
+            self.max.y.max(p.y),
+            self.max.z.max(p.z),
+        );
+    }
+
+    pub fn centroid(&self) -> Point {
+        Point::new(
+            (self.min.x + self.max.x) / 2.,
+            (self.min.y + self.max.y) / 2.,
+            (self.min.z + self.max.z) / 2.,
+        )
+    }
+
+    pub fn surface_area(&self) -> f64 {
+        if self.is_empty() {
+            0.
+        } else {
+            let d = &self.max - &self.min;
+            2. * (d.x * d.y + d.y * d.z + d.z * d.x)
+        }
+    }
+
+    // box of the transformed box (Arvo method), infinite bounds are kept
+    // along the axes they are mapped to
+    pub fn transform(&self, m: &Matrix) -> Aabb {
+        if self.is_empty() {
+            return Aabb::empty();
+        }
+
+        let lo = [self.min.x, self.min.y, self.min.z];
+        let hi = [self.max.x, self.max.y, self.max.z];
+        let mut min = [m.m[0][3], m.m[1][3], m.m[2][3]];
+        let mut max = min;
+
+        for i in 0..3 {
+            for j in 0..3 {
+                // rotations leave tiny terms that must not spread infinity
+                let k = m.m[i][j];
+                if k == 0. || (nearly_zero(k) && !(lo[j].is_finite() && hi[j].is_finite())) {
+                    continue;
+                }
+                let (a, b) = (k * lo[j], k * hi[j]);
+                min[i] += a.min(b);
+                max[i] += a.max(b);
+            }
+        }
+        Aabb {
+            min: Point::new(min[0], min[1], min[2]),
+            max: Point::new(max[0], max[1], max[2]),
+        }
+    }
+
+    // slab method: parameter range of ray inside the box, clipped to
+    // [t_min, t_max]
+    pub fn intersect(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<(f64, f64)> {
+        let (mut t0, mut t1) = (t_min, t_max);
+
+        for (o, v, lo, hi) in [
+            (ray.o.x, ray.v.x, self.min.x, self.max.x),
+            (ray.o.y, ray.v.y, self.min.y, self.max.y),
+            (ray.o.z, ray.v.z, self.min.z, self.max.z),
+        ] {
+            if v == 0. {
+                if o < lo || o > hi {
+                    return None;
+                }
+            } else {
+                let inv = 1. / v;
+                let (mut near, mut far) = ((lo - o) * inv, (hi - o) * inv);
+                if near > far {
+                    std::mem::swap(&mut near, &mut far);
+                }
+                t0 = t0.max(near);
+                t1 = t1.min(far);
+                if t0 > t1 {
+                    return None;
+                }
+            }
+        }
+        Some((t0, t1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{nearly_equal, Vector};
+
+    fn unit_box() -> Aabb {
+        Aabb::new(Point::new(-1., -1., -1.), Point::new(1., 1., 1.))
+    }
+
+    #[test]
+    fn intersect_1() {
+        let ray = Ray::new(Point::new(0., 0., -5.), Vector::new(0., 0., 1.));
+        let (t0, t1) = unit_box().intersect(&ray, 0., f64::INFINITY).unwrap();
+        assert!(nearly_equal(t0, 4.) && nearly_equal(t1, 6.));
+        assert!(unit_box().intersect(&ray, 0., 3.).is_none());
+
+        let ray = Ray::new(Point::new(2., 0., -5.), Vector::new(0., 0., 1.));
+        assert!(unit_box().intersect(&ray, 0., f64::INFINITY).is_none());
+    }
+
+    #[test]
+    fn transform_1() {
+        let m = Matrix::translation(&Vector::new(10., 0., 0.)) * Matrix::rotation_z(45.);
+        let b = unit_box().transform(&m);
+        let r = 2f64.sqrt();
+        assert!(b.min.nearly_equal(&Point::new(10. - r, -r, -1.)));
+        assert!(b.max.nearly_equal(&Point::new(10. + r, r, 1.)));
+    }
+
+    #[test]
+    fn transform_2() {
+        let column = Aabb::new(
+            Point::new(-1., f64::NEG_INFINITY, -1.),
+            Point::new(1., f64::INFINITY, 1.),
+        );
+        let b = column.transform(&Matrix::translation(&Vector::new(1., 2., 3.)));
+        assert!(b.min.x == 0. && b.max.z == 4. && b.max.y == f64::INFINITY);
+
+        let b = column.transform(&Matrix::rotation_x(90.));
+        assert!(b.min.z == f64::NEG_INFINITY && nearly_equal(b.max.y, 1.));
+        assert!(!b.is_finite());
+    }
+
+    #[test]
+    fn union_1() {
+        let mut b = Aabb::empty();
+        assert!(b.is_empty());
+        b.extend(&Point::new(1., 2., 3.));
+        let b = b.union(&unit_box());
+        assert!(b.max.nearly_equal(&Point::new(1., 2., 3.)));
+        assert!(nearly_equal(b.surface_area(), 2. * (6. + 12. + 8.)));
+        assert!(unit_box()
+            .intersection(&Aabb::new(Point::new(2., 2., 2.), Point::new(3., 3., 3.)))
+            .is_empty());
+    }
+}
@@ -0,0 +1,376 @@
+use crate::{Aabb, Ray};
+
+const BINS: usize = 16;
+const MAX_LEAF_SIZE: usize = 8;
+const TRAVERSAL_COST: f64 = 1.; // relative to one primitive intersection
+
+// Bounding volume hierarchy over primitives identified by their index in
+// the bounds slice given to build. Splits are chosen with binned SAH.
+// Primitives with infinite bounds are kept aside and always tested.
+pub struct Bvh {
+    nodes: Vec<Node>,
+    indices: Vec<usize>,
+    unbounded: Vec<usize>,
+}
+
+struct Node {
+    bounds: Aabb,
+    first: usize, // first index of a leaf, or left child of an inner node
+    count: usize, // 0 for inner nodes, right child is first + 1
+}
+
+#[derive(Clone)]
+struct Bin {
+    bounds: Aabb,
+    count: usize,
+}
+
+impl Bvh {
+    pub fn build(bounds: &[Aabb]) -> Bvh {
+        let (mut indices, unbounded): (Vec<usize>, Vec<usize>) =
+            (0..bounds.len()).partition(|i| bounds[*i].is_finite() && !bounds[*i].is_empty());
+        let centroids: Vec<[f64; 3]> = bounds
+            .iter()
+            .map(|b| {
+                let c = b.centroid();
+                [c.x, c.y, c.z]
+            })
+            .collect();
+
+        let mut nodes = vec![];
+        if !indices.is_empty() {
+            nodes.push(Node {
+                bounds: Aabb::empty(),
+                first: 0,
+                count: 0,
+            });
+            let n = indices.len();
+            build_node(&mut nodes, 0, &mut indices, 0, n, bounds, &centroids);
+        }
+
+        Bvh {
+            nodes,
+            indices,
+            unbounded,
+        }
+    }
+
+    pub fn depth(&self) -> usize {
+        fn depth(nodes: &[Node], i: usize) -> usize {
+            if nodes[i].count > 0 {
+                1
+            } else {
+                1 + depth(nodes, nodes[i].first).max(depth(nodes, nodes[i].first + 1))
+            }
+        }
+        if self.nodes.is_empty() {
+            0
+        } else {
+            depth(&self.nodes, 0)
+        }
+    }
+
+    // closest primitive along ray before t_max: hit(i, t_best) returns the
+    // hit distance of primitive i if it is below t_best
+    pub fn closest<F>(&self, ray: &Ray, t_max: f64, mut hit: F) -> Option<(usize, f64)>
+    where
+        F: FnMut(usize, f64) -> Option<f64>,
+    {
+        let mut best: Option<(usize, f64)> = None;
+        let mut t_best = t_max;
+
+        for &i in &self.unbounded {
+            if let Some(t) = hit(i, t_best).filter(|t| *t < t_best) {
+                best = Some((i, t));
+                t_best = t;
+            }
+        }
+
+        if self.nodes.is_empty() || self.nodes[0].bounds.intersect(ray, 0., t_best).is_none() {
+            return best;
+        }
+
+        let mut stack = vec![0];
+        while let Some(n) = stack.pop() {
+            let node = &self.nodes[n];
+            if node.bounds.intersect(ray, 0., t_best).is_none() {
+                continue;
+            }
+
+            if node.count > 0 {
+                for &i in &self.indices[node.first..node.first + node.count] {
+                    if let Some(t) = hit(i, t_best).filter(|t| *t < t_best) {
+                        best = Some((i, t));
+                        t_best = t;
+                    }
+                }
+            } else {
+                // visit nearest child first
+                let (l, r) = (node.first, node.first + 1);
+                let tl = self.nodes[l].bounds.intersect(ray, 0., t_best);
+                let tr = self.nodes[r].bounds.intersect(ray, 0., t_best);
+                match (tl, tr) {
+                    (Some((tl, _)), Some((tr, _))) => {
+                        if tl <= tr {
+                            stack.extend([r, l]);
+                        } else {
+                            stack.extend([l, r]);
+                        }
+                    }
+                    (Some(_), None) => stack.push(l),
+                    (None, Some(_)) => stack.push(r),
+                    (None, None) => (),
+                }
+            }
+        }
+        best
+    }
+
+    // true as soon as hit(i) is true for a primitive whose box is crossed by
+    // ray within [t_min, t_max]
+    pub fn any<F>(&self, ray: &Ray, t_min: f64, t_max: f64, mut hit: F) -> bool
+    where
+        F: FnMut(usize) -> bool,
+    {
+        if self.unbounded.iter().any(|i| hit(*i)) {
+            return true;
+        }
+        if self.nodes.is_empty() {
+            return false;
+        }
+
+        let mut stack = vec![0];
+        while let Some(n) = stack.pop() {
+            let node = &self.nodes[n];
+            if node.bounds.intersect(ray, t_min, t_max).is_none() {
+                continue;
+            }
+
+            if node.count > 0 {
+                if self.indices[node.first..node.first + node.count]
+                    .iter()
+                    .any(|i| hit(*i))
+                {
+                    return true;
+                }
+            } else {
+                stack.extend([node.first, node.first + 1]);
+            }
+        }
+        false
+    }
+}
+
+fn build_node(
+    nodes: &mut Vec<Node>,
+    n: usize,
+    indices: &mut [usize],
+    start: usize,
+    end: usize,
+    bounds: &[Aabb],
+    centroids: &[[f64; 3]],
+) {
+    let prims = &mut indices[start..end];
+    let node_bounds = prims
+        .iter()
+        .fold(Aabb::empty(), |b, i| b.union(&bounds[*i]));
+    let count = prims.len();
+
+    let leaf = |nodes: &mut Vec<Node>, node_bounds: Aabb| {
+        nodes[n] = Node {
+            bounds: node_bounds,
+            first: start,
+            count,
+        };
+    };
+
+    if count <= 2 {
+        return leaf(nodes, node_bounds);
+    }
+
+    let (mut c_min, mut c_max) = ([f64::INFINITY; 3], [f64::NEG_INFINITY; 3]);
+    for i in prims.iter() {
+        for axis in 0..3 {
+            c_min[axis] = c_min[axis].min(centroids[*i][axis]);
+            c_max[axis] = c_max[axis].max(centroids[*i][axis]);
+        }
+    }
+
+    // best split over every axis: (cost, axis, first bin of right side)
+    let mut best: Option<(f64, usize, usize)> = None;
+    for axis in 0..3 {
+        let extent = c_max[axis] - c_min[axis];
+        if extent <= 0. {
+            continue;
+        }
+
+        let mut bins = vec![
+            Bin {
+                bounds: Aabb::empty(),
+                count: 0
+            };
+            BINS
+        ];
+        for i in prims.iter() {
+            let b = bin_index(centroids[*i][axis], c_min[axis], extent);
+            bins[b].bounds = bins[b].bounds.union(&bounds[*i]);
+            bins[b].count += 1;
+        }
+
+        // right side areas and counts, swept from the last bin
+        let mut right = vec![(0., 0); BINS];
+        let (mut acc, mut acc_count) = (Aabb::empty(), 0);
+        for b in (1..BINS).rev() {
+            acc = acc.union(&bins[b].bounds);
+            acc_count += bins[b].count;
+            right[b] = (acc.surface_area(), acc_count);
+        }
+
+        let (mut acc, mut acc_count) = (Aabb::empty(), 0);
+        for b in 1..BINS {
+            acc = acc.union(&bins[b - 1].bounds);
+            acc_count += bins[b - 1].count;
+            let (right_area, right_count) = right[b];
+            if acc_count == 0 || right_count == 0 {
+                continue;
+            }
+
+            let cost = acc.surface_area() * acc_count as f64 + right_area * right_count as f64;
+            if best.is_none_or(|(c, _, _)| cost < c) {
+                best = Some((cost, axis, b));
+            }
+        }
+    }
+
+    let area = node_bounds.surface_area();
+    let Some((cost, axis, split)) = best else {
+        return leaf(nodes, node_bounds);
+    };
+    let split_cost = TRAVERSAL_COST + if area > 0. { cost / area } else { 0. };
+    if split_cost >= count as f64 && count <= MAX_LEAF_SIZE {
+        return leaf(nodes, node_bounds);
+    }
+
+    let extent = c_max[axis] - c_min[axis];
+    let mut mid = 0;
+    for k in 0..count {
+        if bin_index(centroids[prims[k]][axis], c_min[axis], extent) < split {
+            prims.swap(k, mid);
+            mid += 1;
+        }
+    }
+
+    let left = nodes.len();
+    for _ in 0..2 {
+        nodes.push(Node {
+            bounds: Aabb::empty(),
+            first: 0,
+            count: 0,
+        });
+    }
+    nodes[n] = Node {
+        bounds: node_bounds,
+        first: left,
+        count: 0,
+    };
+
+    build_node(nodes, left, indices, start, start + mid, bounds, centroids);
+    build_node(
+        nodes,
+        left + 1,
+        indices,
+        start + mid,
+        end,
+        bounds,
+        centroids,
+    );
+}
+
+fn bin_index(c: f64, min: f64, extent: f64) -> usize {
+    (((c - min) / extent * BINS as f64) as usize).min(BINS - 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Point, Vector};
+
+    // small deterministic generator, good enough for tests
+    struct Lcg(u64);
+
+    impl Lcg {
+        fn next(&mut self) -> f64 {
+            self.0 = self
+                .0
+                .wrapping_mul(6_364_136_223_846_793_005)
+                .wrapping_add(1_442_695_040_888_963_407);
+            (self.0 >> 11) as f64 / (1u64 << 53) as f64
+        }
+    }
+
+    fn random_boxes(n: usize) -> Vec<Aabb> {
+        let mut rng = Lcg(42);
+        (0..n)
+            .map(|_| {
+                let c = Point::new(
+                    rng.next() * 100. - 50.,
+                    rng.next() * 100. - 50.,
+                    rng.next() * 100.,
+                );
+                let h = Vector::new(rng.next(), rng.next(), rng.next());
+                Aabb::new(&c - &h, &c + &h)
+            })
+            .collect()
+    }
+
+    // distance to box entry, used as the primitive intersection
+    fn hit_box(b: &Aabb, ray: &Ray) -> Option<f64> {
+        b.intersect(ray, 0., f64::INFINITY).map(|(t, _)| t)
+    }
+
+    #[test]
+    fn closest_vs_linear() {
+        let boxes = random_boxes(2000);
+        let bvh = Bvh::build(&boxes);
+        assert!(bvh.depth() < 30);
+
+        let mut rng = Lcg(7);
+        for _ in 0..500 {
+            let ray = Ray::new(
+                Point::new(0., 0., -10.),
+                Vector::new(rng.next() - 0.5, rng.next() - 0.5, 1.).unit(),
+            );
+
+            let linear = boxes
+                .iter()
+                .enumerate()
+                .filter_map(|(i, b)| hit_box(b, &ray).map(|t| (i, t)))
+                .min_by(|a, b| a.1.total_cmp(&b.1));
+            let fast = bvh.closest(&ray, f64::INFINITY, |i, _| hit_box(&boxes[i], &ray));
+
+            assert_eq!(linear.map(|h| h.1), fast.map(|h| h.1));
+            assert_eq!(
+                bvh.any(&ray, 0., f64::INFINITY, |i| hit_box(&boxes[i], &ray)
+                    .is_some()),
+                linear.is_some()
+            );
+        }
+    }
+
+    #[test]
+    fn unbounded() {
+        let mut boxes = random_boxes(10);
+        boxes.push(Aabb::infinite());
+        let bvh = Bvh::build(&boxes);
+
+        let ray = Ray::new(Point::new(0., 1000., 0.), Vector::new(0., 1., 0.));
+        let hit = bvh.closest(&ray, f64::INFINITY, |i, _| (i == 10).then_some(1.));
+        assert_eq!(hit, Some((10, 1.)));
+        assert!(bvh.any(&ray, 0., 1., |i| i == 10));
+
+        let empty = Bvh::build(&[]);
+        assert!(empty
+            .closest(&ray, f64::INFINITY, |_, _| Some(1.))
+            .is_none());
+    }
+}
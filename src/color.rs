@@ -0,0 +1,103 @@
+use auto_ops::impl_op_ex;
+use std::fmt;
+
+use crate::nearly_equal;
+
+// Linear RGB color, components are not clamped
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Color {
+    pub r: f64,
+    pub g: f64,
+    pub b: f64,
+}
+
+pub const BLACK: Color = Color::new(0., 0., 0.);
+pub const WHITE: Color = Color::new(1., 1., 1.);
+
+impl Default for Color {
+    fn default() -> Self {
+        BLACK
+    }
+}
+
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "rgb({:.3}, {:.3}, {:.3})", self.r, self.g, self.b)
+    }
+}
+
+impl Color {
+    pub const fn new(r: f64, g: f64, b: f64) -> Color {
+        Color { r, g, b }
+    }
+
+    pub fn nearly_equal(&self, c: &Color) -> bool {
+        nearly_equal(self.r, c.r) && nearly_equal(self.g, c.g) && nearly_equal(self.b, c.b)
+    }
+
+    // 8 bits sRGB encoding (clamped, with sRGB transfer function)
+    pub fn to_srgb8(&self) -> [u8; 3] {
+        [
+            linear_to_srgb8(self.r),
+            linear_to_srgb8(self.g),
+            linear_to_srgb8(self.b),
+        ]
+    }
+}
+
+fn linear_to_srgb8(c: f64) -> u8 {
+    let c = if c.is_nan() { 0. } else { c.clamp(0., 1.) };
+    let s = if c <= 0.003_130_8 {
+        12.92 * c
+    } else {
+        1.055 * c.powf(1. / 2.4) - 0.055
+    };
+
+    (s * 255. + 0.5) as u8
+}
+
+impl_op_ex!(+|lhs: &Color, rhs: &Color| -> Color {
+    Color::new(lhs.r + rhs.r, lhs.g + rhs.g, lhs.b + rhs.b)
+});
+
+impl_op_ex!(+= |lhs: &mut Color, rhs: &Color| {
+    lhs.r += rhs.r;
+    lhs.g += rhs.g;
+    lhs.b += rhs.b;
+});
+
+impl_op_ex!(*|lhs: &Color, rhs: &Color| -> Color {
+    Color::new(lhs.r * rhs.r, lhs.g * rhs.g, lhs.b * rhs.b)
+});
+
+impl_op_ex!(*|lhs: f64, rhs: &Color| -> Color {
+    Color::new(lhs * rhs.r, lhs * rhs.g, lhs * rhs.b)
+});
+
+impl_op_ex!(*= |lhs: &mut Color, rhs: f64| {
+    lhs.r *= rhs;
+    lhs.g *= rhs;
+    lhs.b *= rhs;
+});
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn srgb_1() {
+        assert_eq!(BLACK.to_srgb8(), [0, 0, 0]);
+        assert_eq!(WHITE.to_srgb8(), [255, 255, 255]);
+    }
+
+    #[test]
+    fn srgb_2() {
+        assert_eq!(Color::new(-1., 0.5, 12.).to_srgb8(), [0, 188, 255]);
+    }
+
+    #[test]
+    fn ops_1() {
+        let c = 0.5 * (Color::new(1., 2., 3.) + WHITE) * Color::new(2., 1., 0.);
+        assert!(c.nearly_equal(&Color::new(2., 1.5, 0.)));
+    }
+}
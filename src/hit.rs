@@ -0,0 +1,62 @@
+use std::fmt::Display;
+
+use crate::math::{Point, Vector};
+
+pub struct Hit {
+    pub t: f64,
+    pub point: Point,           // hit point in camera cs
+    pub world_point: Point,     // hit point in reference cs
+    pub normal: Vector,         // unit normal in camera cs, facing the incoming ray
+    pub front_face: bool,       // true if the ray hit the outer side of the surface
+    pub uv: Option<(f64, f64)>, // surface coordinates, when the shape has some
+}
+
+impl Display for Hit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "t: {:.2} p: {} n: {} {}",
+            self.t,
+            self.point,
+            self.normal,
+            if self.front_face { "front" } else { "back" }
+        )
+    }
+}
+
+impl Hit {
+    // outward is the outward surface normal in camera cs, v the ray direction
+    pub fn new(t: f64, point: Point, world_point: Point, outward: Vector, v: &Vector) -> Hit {
+        let outward = outward.unit();
+        let front_face = &outward * v < 0.;
+
+        Hit {
+            t,
+            point,
+            world_point,
+            normal: if front_face { outward } else { -outward },
+            front_face,
+            uv: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{J, O};
+
+    #[test]
+    fn front_face() {
+        let h = Hit::new(1., O, O, J, &Vector::new(0., -1., 0.));
+        assert!(h.front_face);
+        assert!(h.normal.nearly_equal(&J));
+    }
+
+    #[test]
+    fn back_face() {
+        let h = Hit::new(1., O, O, J, &Vector::new(0., 1., 0.));
+        assert!(!h.front_face);
+        assert!(h.normal.nearly_equal(&-J));
+    }
+}
@@ -0,0 +1,108 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use crate::pinhole::ImageSize;
+use crate::Color;
+
+// Framebuffer of linear RGB pixels, stored row by row from the top left corner
+pub struct Image {
+    size: ImageSize,
+    pixels: Vec<Color>,
+}
+
+impl Image {
+    pub fn new(size: &ImageSize) -> Image {
+        let n = (size.width as usize)
+            .checked_mul(size.height as usize)
+            .expect("image too large");
+        Image {
+            size: *size,
+            pixels: vec![Color::default(); n],
+        }
+    }
+
+    pub fn get_size(&self) -> &ImageSize {
+        &self.size
+    }
+
+    pub fn width(&self) -> u32 {
+        self.size.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.size.height
+    }
+
+    pub fn get(&self, x: u32, y: u32) -> &Color {
+        &self.pixels[self.index(x, y)]
+    }
+
+    pub fn set(&mut self, x: u32, y: u32, c: Color) {
+        let i = self.index(x, y);
+        self.pixels[i] = c;
+    }
+
+    pub fn pixels(&self) -> &[Color] {
+        &self.pixels
+    }
+
+    fn index(&self, x: u32, y: u32) -> usize {
+        assert!(x < self.size.width && y < self.size.height);
+        y as usize * self.size.width as usize + x as usize
+    }
+
+    pub fn write_ppm<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        ppm::write(self, w, false)
+    }
+
+    pub fn write_ppm_ascii<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        ppm::write(self, w, true)
+    }
+
+    pub fn write_png<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        png::write(self, w)
+    }
+
+    // Format is chosen from file extension: .png, or .ppm (binary) by default
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let path = path.as_ref();
+        let mut w = BufWriter::new(File::create(path)?);
+
+        match path.extension().and_then(|e| e.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("png") => self.write_png(&mut w)?,
+            _ => self.write_ppm(&mut w)?,
+        }
+        w.flush()
+    }
+}
+
+mod accumulator;
+mod filter;
+mod png;
+mod ppm;
+
+pub use accumulator::Accumulator;
+pub use filter::{Filter, MAX_RADIUS};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::WHITE;
+
+    #[test]
+    fn set_get() {
+        let mut img = Image::new(&ImageSize::new(3, 2));
+        img.set(2, 1, WHITE);
+        assert_eq!(*img.get(2, 1), WHITE);
+        assert_eq!(img.pixels()[5], WHITE);
+        assert_eq!(*img.get(0, 0), Color::default());
+    }
+
+    #[test]
+    #[should_panic]
+    fn out_of_bounds() {
+        let img = Image::new(&ImageSize::new(3, 2));
+        img.get(3, 0);
+    }
+}
@@ -0,0 +1,204 @@
+use super::{Filter, Image, MAX_RADIUS};
+use crate::pinhole::ImageSize;
+use crate::{Color, Sample, Window};
+
+// Weighted sums of filtered samples: each sample is splat into every pixel
+// whose center lies within the filter radius. Pixel values are the color
+// sums divided by the weight sums. An accumulator may hold only a window of
+// the image, partial accumulators are then merged.
+pub struct Accumulator {
+    size: ImageSize,
+    window: Window,
+    filter: Filter,
+    radius: f64,
+    colors: Vec<Color>,
+    weights: Vec<f64>,
+}
+
+impl Accumulator {
+    pub fn new(size: &ImageSize, filter: Filter, radius: f64) -> Accumulator {
+        Accumulator::with_window(size, &Window::full(size), filter, radius)
+    }
+
+    pub fn with_window(
+        size: &ImageSize,
+        window: &Window,
+        filter: Filter,
+        radius: f64,
+    ) -> Accumulator {
+        assert!(radius > 0. && radius <= MAX_RADIUS);
+        assert!(window.x1 <= size.width && window.y1 <= size.height);
+        let n = (window.width() as usize)
+            .checked_mul(window.height() as usize)
+            .expect("image too large");
+
+        Accumulator {
+            size: *size,
+            window: *window,
+            filter,
+            radius,
+            colors: vec![Color::default(); n],
+            weights: vec![0.; n],
+        }
+    }
+
+    pub fn get_size(&self) -> &ImageSize {
+        &self.size
+    }
+
+    pub fn get_window(&self) -> &Window {
+        &self.window
+    }
+
+    // window of the pixels reached by samples taken in w
+    pub fn reach(&self, w: &Window) -> Window {
+        let r = self.radius.ceil() as u32;
+        Window::new(
+            w.x0.saturating_sub(r),
+            w.y0.saturating_sub(r),
+            (w.x1 + r).min(self.size.width),
+            (w.y1 + r).min(self.size.height),
+        )
+    }
+
+    pub fn get_filter(&self) -> Filter {
+        self.filter
+    }
+
+    pub fn get_radius(&self) -> f64 {
+        self.radius
+    }
+
+    pub fn add(&mut self, sample: &Sample, c: &Color) {
+        let (px, py) = sample.position();
+        self.splat(px, py, c);
+    }
+
+    // color c seen at (px, py) in continuous image coordinates: pixel (x, y)
+    // covers [x, x + 1) x [y, y + 1)
+    pub fn splat(&mut self, px: f64, py: f64, c: &Color) {
+        let (r, w) = (self.radius, &self.window);
+        let x0 = (px - 0.5 - r).ceil().max(w.x0 as f64) as i64;
+        let y0 = (py - 0.5 - r).ceil().max(w.y0 as f64) as i64;
+        let x1 = ((px - 0.5 + r).floor() as i64).min(w.x1 as i64 - 1);
+        let y1 = ((py - 0.5 + r).floor() as i64).min(w.y1 as i64 - 1);
+
+        for y in y0..=y1 {
+            for x in x0..=x1 {
+                let w = self
+                    .filter
+                    .weight(x as f64 + 0.5 - px, y as f64 + 0.5 - py, self.radius);
+                if w != 0. {
+                    let i = self.index(x as u32, y as u32);
+                    self.colors[i] += w * c;
+                    self.weights[i] += w;
+                }
+            }
+        }
+    }
+
+    pub fn weight(&self, x: u32, y: u32) -> f64 {
+        self.weights[self.index(x, y)]
+    }
+
+    // add the sums of a partial accumulator, its window must be inside ours
+    pub fn merge(&mut self, other: &Accumulator) {
+        let w = &other.window;
+        assert!(self.window.contains(w.x0, w.y0) && self.window.contains(w.x1 - 1, w.y1 - 1));
+
+        for y in w.y0..w.y1 {
+            for x in w.x0..w.x1 {
+                let (i, j) = (self.index(x, y), other.index(x, y));
+                self.colors[i] += other.colors[j];
+                self.weights[i] += other.weights[j];
+            }
+        }
+    }
+
+    // pixels without any weight, or out of the window, are black
+    pub fn to_image(&self) -> Image {
+        let mut img = Image::new(&self.size);
+        let w = self.window;
+        for y in w.y0..w.y1 {
+            for x in w.x0..w.x1 {
+                let i = self.index(x, y);
+                if self.weights[i].abs() > 1e-12 {
+                    img.set(x, y, (1. / self.weights[i]) * self.colors[i]);
+                }
+            }
+        }
+        img
+    }
+
+    fn index(&self, x: u32, y: u32) -> usize {
+        let w = &self.window;
+        assert!(w.contains(x, y));
+        (y - w.y0) as usize * w.width() as usize + (x - w.x0) as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{nearly_equal, BLACK, WHITE};
+
+    fn size(width: u32, height: u32) -> ImageSize {
+        ImageSize { width, height }
+    }
+
+    #[test]
+    fn box_average() {
+        let mut acc = Accumulator::new(&size(2, 1), Filter::Box, 0.5);
+        acc.splat(0.25, 0.5, &WHITE);
+        acc.splat(0.75, 0.5, &BLACK);
+        acc.splat(1.5, 0.5, &WHITE);
+
+        assert!(nearly_equal(acc.weight(0, 0), 2.));
+        let img = acc.to_image();
+        assert!(img.get(0, 0).nearly_equal(&(0.5 * WHITE)));
+        assert!(img.get(1, 0).nearly_equal(&WHITE));
+    }
+
+    #[test]
+    fn tent_splat() {
+        let mut acc = Accumulator::new(&size(3, 3), Filter::Tent, 1.);
+        // on the corner shared by 4 pixels
+        acc.splat(1., 1., &WHITE);
+        for (x, y) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+            assert!(nearly_equal(acc.weight(x, y), 0.25));
+        }
+        assert_eq!(acc.weight(2, 2), 0.);
+        assert!(acc.to_image().get(1, 1).nearly_equal(&WHITE));
+        assert_eq!(*acc.to_image().get(2, 2), BLACK);
+
+        // samples out of the image still reach the border pixels
+        acc.splat(3.2, 2.5, &WHITE);
+        assert!(nearly_equal(acc.weight(2, 2), 0.3));
+    }
+
+    #[test]
+    fn merge() {
+        let size = size(4, 4);
+        let mut full = Accumulator::new(&size, Filter::Tent, 1.);
+        let mut acc = Accumulator::new(&size, Filter::Tent, 1.);
+
+        // samples of the left and right halves, splat into windows reaching
+        // one pixel further
+        let halves = [Window::new(0, 0, 2, 4), Window::new(2, 0, 4, 4)];
+        for half in halves {
+            let mut part = Accumulator::with_window(&size, &acc.reach(&half), Filter::Tent, 1.);
+            assert_eq!(part.get_window().width(), 3);
+            for (px, py) in [(half.x0 as f64 + 0.7, 1.2), (half.x0 as f64 + 1.9, 2.5)] {
+                part.splat(px, py, &WHITE);
+                full.splat(px, py, &WHITE);
+            }
+            acc.merge(&part);
+        }
+
+        for y in 0..4 {
+            for x in 0..4 {
+                assert!(nearly_equal(acc.weight(x, y), full.weight(x, y)));
+            }
+        }
+    }
+}
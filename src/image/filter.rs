@@ -0,0 +1,156 @@
+use std::f64::consts::PI;
+use std::fmt::Display;
+use std::str::FromStr;
+
+// Reconstruction filter kernels, separable: the weight of a sample at
+// (dx, dy) pixels from a pixel center is eval(dx, r) * eval(dy, r). Every
+// kernel is zero from the radius r on.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Filter {
+    #[default]
+    Box,
+    Tent,
+    Gaussian, // alpha = 2, shifted to reach zero at the radius
+    Mitchell, // B = C = 1/3, stretched over the radius
+    Lanczos,  // sinc windowed by a sinc of width the radius
+}
+
+// widest kernel in pixels: every sample is splatted on (2 r)² pixels
+pub const MAX_RADIUS: f64 = 16.;
+
+const FILTERS: [(&str, Filter); 5] = [
+    ("box", Filter::Box),
+    ("tent", Filter::Tent),
+    ("gaussian", Filter::Gaussian),
+    ("mitchell", Filter::Mitchell),
+    ("lanczos", Filter::Lanczos),
+];
+
+impl Display for Filter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (name, _) = FILTERS.iter().find(|(_, k)| k == self).unwrap();
+        write!(f, "{name}")
+    }
+}
+
+impl FromStr for Filter {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        FILTERS
+            .iter()
+            .find(|(name, _)| *name == s)
+            .map(|(_, k)| *k)
+            .ok_or_else(|| format!("unknown filter '{s}'"))
+    }
+}
+
+impl Filter {
+    // usual radius in pixels, box of radius 0.5 is a plain average
+    pub fn default_radius(&self) -> f64 {
+        match self {
+            Filter::Box => 0.5,
+            Filter::Tent => 1.,
+            Filter::Gaussian => 1.5,
+            Filter::Mitchell | Filter::Lanczos => 2.,
+        }
+    }
+
+    // 1D kernel at x pixels from the center
+    pub fn eval(&self, x: f64, radius: f64) -> f64 {
+        let x = x.abs();
+        if x >= radius {
+            return 0.;
+        }
+
+        match self {
+            Filter::Box => 1.,
+            Filter::Tent => 1. - x / radius,
+            Filter::Gaussian => {
+                const ALPHA: f64 = 2.;
+                (-ALPHA * x * x).exp() - (-ALPHA * radius * radius).exp()
+            }
+            Filter::Mitchell => mitchell(2. * x / radius),
+            Filter::Lanczos => sinc(x) * sinc(x / radius),
+        }
+    }
+
+    pub fn weight(&self, dx: f64, dy: f64, radius: f64) -> f64 {
+        self.eval(dx, radius) * self.eval(dy, radius)
+    }
+}
+
+// Mitchell-Netravali on [0, 2)
+fn mitchell(x: f64) -> f64 {
+    const B: f64 = 1. / 3.;
+    const C: f64 = 1. / 3.;
+
+    let x2 = x * x;
+    let k = if x < 1. {
+        (12. - 9. * B - 6. * C) * x2 * x + (-18. + 12. * B + 6. * C) * x2 + (6. - 2. * B)
+    } else {
+        (-B - 6. * C) * x2 * x
+            + (6. * B + 30. * C) * x2
+            + (-12. * B - 48. * C) * x
+            + (8. * B + 24. * C)
+    };
+    k / 6.
+}
+
+fn sinc(x: f64) -> f64 {
+    if x.abs() < 1e-5 {
+        1.
+    } else {
+        (PI * x).sin() / (PI * x)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nearly_equal;
+
+    #[test]
+    fn kernels() {
+        assert_eq!(Filter::Box.eval(0.49, 0.5), 1.);
+        assert_eq!(Filter::Box.eval(0.5, 0.5), 0.);
+        assert!(nearly_equal(Filter::Tent.eval(-0.25, 1.), 0.75));
+        assert!(nearly_equal(
+            Filter::Gaussian.eval(0., 1.5),
+            1. - (-4.5f64).exp()
+        ));
+        assert!(Filter::Gaussian.eval(1.4999, 1.5) < 1e-4);
+        assert!(nearly_equal(Filter::Mitchell.eval(0., 2.), 8. / 9.));
+        assert!(nearly_equal(Filter::Mitchell.eval(1., 2.), 1. / 18.));
+        assert!(Filter::Mitchell.eval(1.5, 2.) < 0.);
+        assert!(nearly_equal(Filter::Lanczos.eval(0., 2.), 1.));
+        assert!(Filter::Lanczos.eval(1., 2.).abs() < 1e-12);
+        assert!(nearly_equal(Filter::Tent.weight(0.5, 0.5, 1.), 0.25));
+    }
+
+    #[test]
+    fn continuity() {
+        // kernels are continuous, with no jump at the radius
+        for f in [
+            Filter::Tent,
+            Filter::Gaussian,
+            Filter::Mitchell,
+            Filter::Lanczos,
+        ] {
+            let r = f.default_radius();
+            assert!(f.eval(r - 1e-9, r).abs() < 1e-6, "{f}");
+            assert!(
+                (f.eval(1. - 1e-9, r) - f.eval(1. + 1e-9, r)).abs() < 1e-6,
+                "{f}"
+            );
+        }
+    }
+
+    #[test]
+    fn names() {
+        for f in FILTERS.map(|(_, f)| f) {
+            assert_eq!(f.to_string().parse::<Filter>(), Ok(f));
+        }
+        assert!("sinc".parse::<Filter>().is_err());
+    }
+}
@@ -0,0 +1,126 @@
+use std::io::{self, Write};
+
+use super::Image;
+
+// Minimal PNG encoder: 8 bits RGB, no interlacing, zlib stream made of
+// stored (uncompressed) deflate blocks.
+
+const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+const MAX_STORED_BLOCK: usize = 65535;
+
+pub fn write<W: Write>(img: &Image, w: &mut W) -> io::Result<()> {
+    w.write_all(&SIGNATURE)?;
+
+    let mut ihdr = Vec::with_capacity(13);
+    ihdr.extend_from_slice(&img.width().to_be_bytes());
+    ihdr.extend_from_slice(&img.height().to_be_bytes());
+    ihdr.extend_from_slice(&[8, 2, 0, 0, 0]); // depth, rgb, deflate, no filter, no interlace
+    write_chunk(w, b"IHDR", &ihdr)?;
+
+    let mut raw = Vec::with_capacity((img.width() as usize * 3 + 1) * img.height() as usize);
+    for row in img.pixels().chunks(img.width() as usize) {
+        raw.push(0); // filter type: none
+        raw.extend(row.iter().flat_map(|c| c.to_srgb8()));
+    }
+    write_chunk(w, b"IDAT", &zlib_stored(&raw))?;
+    write_chunk(w, b"IEND", &[])
+}
+
+fn write_chunk<W: Write>(w: &mut W, kind: &[u8; 4], data: &[u8]) -> io::Result<()> {
+    w.write_all(&(data.len() as u32).to_be_bytes())?;
+    w.write_all(kind)?;
+    w.write_all(data)?;
+
+    let crc = crc32_update(crc32_update(0xffff_ffff, kind), data) ^ 0xffff_ffff;
+    w.write_all(&crc.to_be_bytes())
+}
+
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(data.len() + data.len() / MAX_STORED_BLOCK * 5 + 16);
+    out.extend_from_slice(&[0x78, 0x01]);
+
+    let mut blocks = data.chunks(MAX_STORED_BLOCK).peekable();
+    if blocks.peek().is_none() {
+        out.extend_from_slice(&[1, 0, 0, 0xff, 0xff]);
+    }
+    while let Some(block) = blocks.next() {
+        let last = blocks.peek().is_none();
+        let len = block.len() as u16;
+        out.push(last as u8);
+        out.extend_from_slice(&len.to_le_bytes());
+        out.extend_from_slice(&(!len).to_le_bytes());
+        out.extend_from_slice(block);
+    }
+
+    out.extend_from_slice(&adler32(data).to_be_bytes());
+    out
+}
+
+fn crc32_update(mut crc: u32, data: &[u8]) -> u32 {
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                0xedb8_8320 ^ (crc >> 1)
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    crc
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for chunk in data.chunks(5552) {
+        for &byte in chunk {
+            a += byte as u32;
+            b += a;
+        }
+        a %= 65521;
+        b %= 65521;
+    }
+    (b << 16) | a
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pinhole::ImageSize;
+
+    #[test]
+    fn crc32_1() {
+        let crc = crc32_update(0xffff_ffff, b"IEND") ^ 0xffff_ffff;
+        assert_eq!(crc, 0xae42_6082);
+    }
+
+    #[test]
+    fn adler32_1() {
+        assert_eq!(adler32(b"Wikipedia"), 0x11e6_0398);
+    }
+
+    #[test]
+    fn stored_blocks() {
+        let data = vec![7u8; MAX_STORED_BLOCK + 10];
+        let z = zlib_stored(&data);
+        assert_eq!(z.len(), 2 + 5 + MAX_STORED_BLOCK + 5 + 10 + 4);
+        assert_eq!(z[2], 0);
+        assert_eq!(z[2 + 5 + MAX_STORED_BLOCK], 1);
+    }
+
+    #[test]
+    fn png_layout() {
+        let img = Image::new(&ImageSize::new(4, 3));
+        let mut out = Vec::new();
+        img.write_png(&mut out).unwrap();
+
+        assert_eq!(out[..8], SIGNATURE);
+        assert_eq!(&out[12..16], b"IHDR");
+        assert_eq!(out[16..20], 4u32.to_be_bytes());
+        assert_eq!(out[20..24], 3u32.to_be_bytes());
+        assert_eq!(
+            out[out.len() - 12..],
+            [0, 0, 0, 0, b'I', b'E', b'N', b'D', 0xae, 0x42, 0x60, 0x82]
+        );
+    }
+}
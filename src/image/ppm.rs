@@ -0,0 +1,53 @@
+use std::io::{self, Write};
+
+use super::Image;
+
+// Netpbm color image: P6 (binary) or P3 (ascii), 8 bits sRGB samples
+pub fn write<W: Write>(img: &Image, w: &mut W, ascii: bool) -> io::Result<()> {
+    let magic = if ascii { "P3" } else { "P6" };
+    write!(w, "{magic}\n{} {}\n255\n", img.width(), img.height())?;
+
+    if ascii {
+        for row in img.pixels().chunks(img.width() as usize) {
+            let line: Vec<String> = row
+                .iter()
+                .map(|c| {
+                    let [r, g, b] = c.to_srgb8();
+                    format!("{r} {g} {b}")
+                })
+                .collect();
+            writeln!(w, "{}", line.join(" "))?;
+        }
+    } else {
+        let data: Vec<u8> = img.pixels().iter().flat_map(|c| c.to_srgb8()).collect();
+        w.write_all(&data)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::pinhole::ImageSize;
+    use crate::{Color, Image, WHITE};
+
+    #[test]
+    fn ascii() {
+        let mut img = Image::new(&ImageSize::new(2, 1));
+        img.set(1, 0, WHITE);
+        let mut out = Vec::new();
+        img.write_ppm_ascii(&mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "P3\n2 1\n255\n0 0 0 255 255 255\n"
+        );
+    }
+
+    #[test]
+    fn binary() {
+        let mut img = Image::new(&ImageSize::new(1, 2));
+        img.set(0, 1, Color::new(1., 0., 1.));
+        let mut out = Vec::new();
+        img.write_ppm(&mut out).unwrap();
+        assert_eq!(out, b"P6\n1 2\n255\n\x00\x00\x00\xff\x00\xff");
+    }
+}
@@ -1,12 +1,37 @@
+mod aabb;
+mod bvh;
+mod color;
+mod hit;
+mod image;
+mod light;
+mod material;
 mod math;
+mod obj;
 mod pinhole;
 mod ray;
+mod renderer;
+mod scene;
+mod shader;
 mod shapes;
 
 pub use math::{deg_to_rad, nearly_equal, nearly_zero, rad_to_deg};
-pub use math::{Cs, Matrix, Point, SphCoord, Vector};
+pub use math::{solve_cubic, solve_quadratic, solve_quartic};
+pub use math::{Bound, Cs, EulerOrder, Interval, IntervalList, Matrix, Point, Quaternion};
+pub use math::{SphCoord, Vector, ID_QUATERNION};
 pub use math::{I, J, K, O, POINT_I, POINT_J, POINT_K, VEC_0};
 
-pub use pinhole::{Camera, Focale};
+pub use aabb::Aabb;
+pub use bvh::Bvh;
+pub use color::{Color, BLACK, WHITE};
+pub use hit::Hit;
+pub use image::{Accumulator, Filter, Image, MAX_RADIUS};
+pub use light::Light;
+pub use material::Material;
+pub use obj::{parse_mtl, Obj, ObjGroup};
+pub use pinhole::{Camera, Focale, ImageSize, Pattern, Sample, Sampler, Window, MAX_PIXELS};
 pub use ray::Ray;
-pub use shapes::{Ball, Cylinder, Shapes};
+pub use renderer::{Progress, Renderer};
+pub use scene::{ParseError, Scene};
+pub use shader::BlinnPhong;
+pub use shapes::{Ball, Cone, Csg, CsgOp, Cuboid, Cylinder, Disk, Mesh, Plane, Shapes};
+pub use shapes::{Torus, Triangle};
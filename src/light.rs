@@ -0,0 +1,128 @@
+use crate::{deg_to_rad, Color, Matrix, Point, Vector};
+
+// Light sources, positions and directions are expressed in reference cs.
+// Directions are the ones light travels along.
+#[derive(Clone, Debug)]
+pub enum Light {
+    Point {
+        position: Point,
+        color: Color,
+    },
+    Directional {
+        direction: Vector,
+        color: Color,
+    },
+    Spot {
+        position: Point,
+        direction: Vector,
+        angle: f64, // cone half angle, in degrees
+        exponent: f64,
+        color: Color,
+    },
+}
+
+impl Light {
+    // same light expressed in another cs, m being the change of cs matrix
+    pub fn transform(&self, m: &Matrix) -> Light {
+        match self {
+            Light::Point { position, color } => Light::Point {
+                position: m * position,
+                color: *color,
+            },
+            Light::Directional { direction, color } => Light::Directional {
+                direction: m * direction,
+                color: *color,
+            },
+            Light::Spot {
+                position,
+                direction,
+                angle,
+                exponent,
+                color,
+            } => Light::Spot {
+                position: m * position,
+                direction: m * direction,
+                angle: *angle,
+                exponent: *exponent,
+                color: *color,
+            },
+        }
+    }
+
+    // unit vector from p toward the light, distance to the light and
+    // intensity received at p, or None if p is not lit
+    pub fn illuminate(&self, p: &Point) -> Option<(Vector, f64, Color)> {
+        match self {
+            Light::Point { position, color } => {
+                let l = position - p;
+                let dist = l.length();
+                Some((l.unit(), dist, *color))
+            }
+            Light::Directional { direction, color } => {
+                Some((-direction.clone().unit(), f64::INFINITY, *color))
+            }
+            Light::Spot {
+                position,
+                direction,
+                angle,
+                exponent,
+                color,
+            } => {
+                let l = position - p;
+                let dist = l.length();
+                let l = l.unit();
+                let cos = -(&l * &direction.clone().unit());
+                if cos < deg_to_rad(*angle).cos() {
+                    None
+                } else {
+                    Some((l, dist, cos.powf(*exponent) * color))
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{nearly_equal, WHITE};
+
+    #[test]
+    fn point_1() {
+        let light = Light::Point {
+            position: Point::new(0., 10., 0.),
+            color: WHITE,
+        };
+        let (l, dist, c) = light.illuminate(&Point::new(0., 0., 0.)).unwrap();
+        assert!(l.nearly_equal(&Vector::new(0., 1., 0.)));
+        assert!(nearly_equal(dist, 10.));
+        assert_eq!(c, WHITE);
+    }
+
+    #[test]
+    fn spot_1() {
+        let light = Light::Spot {
+            position: Point::new(0., 10., 0.),
+            direction: Vector::new(0., -1., 0.),
+            angle: 30.,
+            exponent: 1.,
+            color: WHITE,
+        };
+        assert!(light.illuminate(&Point::new(0., 0., 0.)).is_some());
+        assert!(light.illuminate(&Point::new(10., 0., 0.)).is_none());
+    }
+
+    #[test]
+    fn transform_1() {
+        let light = Light::Directional {
+            direction: Vector::new(0., -1., 0.),
+            color: WHITE,
+        };
+        let m = Matrix::translation(&Vector::new(1., 2., 3.)) * Matrix::rotation_z(90.);
+        let (l, _, _) = light
+            .transform(&m)
+            .illuminate(&Point::new(0., 0., 0.))
+            .unwrap();
+        assert!(l.nearly_equal(&Vector::new(-1., 0., 0.)));
+    }
+}
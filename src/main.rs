@@ -0,0 +1,320 @@
+use std::process::ExitCode;
+use std::time::Duration;
+
+use cg::{BlinnPhong, Filter, Focale, Hit, ImageSize, Pattern, Ray, Renderer, Scene, Window};
+use cg::{MAX_PIXELS, MAX_RADIUS};
+
+const USAGE: &str = "usage: cg [options] <scene file>
+
+options:
+  -o, --output <file>   output image, .png or .ppm (default: out.png)
+  -W, --width <n>       image width in pixels
+  -H, --height <n>      image height in pixels, at most 8192x8192 pixels
+  -a, --angle <deg>     horizontal field of view in degrees
+  -s, --spp <n>         samples per pixel
+  -p, --pattern <name>  sample pattern: regular, jittered, random, halton,
+                        sobol or r2 (default: regular)
+  -f, --filter <name>   reconstruction filter: box, tent, gaussian, mitchell
+                        or lanczos (default: box)
+  -r, --radius <px>     filter radius in pixels, at most 16 (default: filter
+                        dependent)
+  -c, --crop <x0,y0,x1,y1>
+                        render only pixels x0 <= x < x1, y0 <= y < y1
+  -d, --depth <n>       maximum reflection/refraction depth
+  -j, --threads <n>     render threads (default: available cores)
+  -P, --progressive     render in passes of increasing samples per pixel,
+                        writing the output after each pass; the pattern
+                        defaults to sobol, regular and jittered are refused
+  -t, --time <s>        progressive render stopping after s seconds, or
+                        once --spp samples per pixel are taken if given
+  -h, --help            print this help";
+
+#[derive(Debug, Default, PartialEq)]
+struct Options {
+    scene: String,
+    output: String,
+    width: Option<u32>,
+    height: Option<u32>,
+    angle: Option<f64>,
+    spp: Option<u32>,
+    pattern: Option<Pattern>,
+    filter: Option<Filter>,
+    radius: Option<f64>,
+    crop: Option<Window>,
+    depth: Option<u32>,
+    threads: Option<u32>,
+    progressive: bool,
+    time: Option<f64>,
+}
+
+fn parse_args<I: Iterator<Item = String>>(mut args: I) -> Result<Option<Options>, String> {
+    let mut opts = Options {
+        output: "out.png".to_owned(),
+        ..Options::default()
+    };
+    let mut scene = None;
+
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| {
+            args.next()
+                .ok_or_else(|| format!("missing value for option {name}"))
+        };
+
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(None),
+            "-o" | "--output" => opts.output = value(&arg)?,
+            "-W" | "--width" => opts.width = Some(parse_count(&arg, &value(&arg)?)?),
+            "-H" | "--height" => opts.height = Some(parse_count(&arg, &value(&arg)?)?),
+            "-s" | "--spp" => opts.spp = Some(parse_count(&arg, &value(&arg)?)?),
+            "-p" | "--pattern" => opts.pattern = Some(value(&arg)?.parse()?),
+            "-f" | "--filter" => opts.filter = Some(value(&arg)?.parse()?),
+            "-r" | "--radius" => {
+                let v = value(&arg)?;
+                match v.parse::<f64>() {
+                    Ok(radius) if radius > 0. && radius <= MAX_RADIUS => opts.radius = Some(radius),
+                    _ => {
+                        return Err(format!(
+                            "invalid radius '{v}', expected (0, {MAX_RADIUS}] pixels"
+                        ))
+                    }
+                }
+            }
+            "-j" | "--threads" => opts.threads = Some(parse_count(&arg, &value(&arg)?)?),
+            "-P" | "--progressive" => opts.progressive = true,
+            "-t" | "--time" => {
+                let v = value(&arg)?;
+                match v.parse::<f64>() {
+                    Ok(time) if time > 0. && time.is_finite() => opts.time = Some(time),
+                    _ => return Err(format!("invalid time '{v}', expected seconds")),
+                }
+            }
+            "-c" | "--crop" => opts.crop = Some(parse_window(&value(&arg)?)?),
+            "-d" | "--depth" => {
+                let v = value(&arg)?;
+                match v.parse::<u32>() {
+                    Ok(depth) => opts.depth = Some(depth),
+                    _ => return Err(format!("invalid value '{v}' for {arg}")),
+                }
+            }
+            "-a" | "--angle" => {
+                let v = value(&arg)?;
+                match v.parse::<f64>() {
+                    Ok(angle) if angle > 0. && angle < 180. => opts.angle = Some(angle),
+                    _ => return Err(format!("invalid angle '{v}', expected (0, 180) degrees")),
+                }
+            }
+            s if s.starts_with('-') => return Err(format!("unknown option {s}")),
+            s => {
+                if scene.replace(s.to_owned()).is_some() {
+                    return Err("only one scene file expected".to_owned());
+                }
+            }
+        }
+    }
+
+    opts.scene = scene.ok_or("missing scene file")?;
+    let size = ImageSize::new(opts.width.unwrap_or(1), opts.height.unwrap_or(1));
+    if size.is_too_large() {
+        return Err(format!("image is larger than {MAX_PIXELS} pixels"));
+    }
+    if opts.progressive || opts.time.is_some() {
+        if let Some(p) = opts.pattern.filter(|p| !p.is_progressive()) {
+            return Err(format!(
+                "pattern {p} cannot be rendered progressively, use random, halton, sobol or r2"
+            ));
+        }
+    }
+    Ok(Some(opts))
+}
+
+fn parse_count(name: &str, v: &str) -> Result<u32, String> {
+    match v.parse::<u32>() {
+        Ok(n) if n > 0 => Ok(n),
+        _ => Err(format!(
+            "invalid value '{v}' for {name}, expected positive integer"
+        )),
+    }
+}
+
+fn parse_window(v: &str) -> Result<Window, String> {
+    let k: Vec<u32> = v
+        .split(',')
+        .map(|k| k.trim().parse::<u32>())
+        .collect::<Result<_, _>>()
+        .map_err(|_| format!("invalid crop window '{v}'"))?;
+    match k[..] {
+        [x0, y0, x1, y1] if x0 < x1 && y0 < y1 => Ok(Window::new(x0, y0, x1, y1)),
+        _ => Err(format!("invalid crop window '{v}', expected x0,y0,x1,y1")),
+    }
+}
+
+fn run(opts: &Options) -> Result<(), String> {
+    let src = std::fs::read_to_string(&opts.scene)
+        .map_err(|e| format!("cannot read {}: {e}", opts.scene))?;
+    let mut scene = Scene::parse(&src).map_err(|e| format!("{}: {e}", opts.scene))?;
+
+    let size = *scene.camera.get_image_size();
+    scene.camera.set_image_size(
+        opts.width.unwrap_or(size.width),
+        opts.height.unwrap_or(size.height),
+    );
+    let size = scene.camera.get_image_size();
+    if size.is_too_large() {
+        return Err(format!(
+            "{}x{} image is larger than {MAX_PIXELS} pixels",
+            size.width, size.height
+        ));
+    }
+    if let Some(crop) = opts.crop {
+        let size = scene.camera.get_image_size();
+        if crop.x1 > size.width || crop.y1 > size.height {
+            return Err(format!(
+                "crop window is outside the {}x{} image",
+                size.width, size.height
+            ));
+        }
+        scene.camera.set_crop(Some(crop));
+    }
+    if let Some(angle) = opts.angle {
+        scene.camera.set_focale(Focale::AngleDeg(angle));
+    }
+    // a time budget alone renders until it is spent
+    let progressive = opts.progressive || opts.time.is_some();
+    if let Some(spp) = opts.spp {
+        scene.camera.set_samples(spp);
+    } else if opts.time.is_some() {
+        scene.camera.set_samples(u32::MAX);
+    }
+    if let Some(pattern) = opts.pattern {
+        scene.camera.set_pattern(pattern);
+    } else if progressive {
+        scene.camera.set_pattern(Pattern::Sobol);
+    }
+    if opts.filter.is_some() || opts.radius.is_some() {
+        let filter = opts.filter.unwrap_or_default();
+        let radius = opts.radius.unwrap_or(filter.default_radius());
+        scene.camera.set_filter(filter, radius);
+    }
+
+    let mut renderer = Renderer::new(scene.camera, scene.shapes)
+        .map_err(|e| format!("{}: invalid camera: {e}", opts.scene))?;
+    if let Some(threads) = opts.threads {
+        renderer.set_threads(threads as usize);
+    }
+    let mut shader = BlinnPhong::new(renderer.get_camera(), &scene.lights, scene.materials);
+    if let Some(depth) = opts.depth {
+        shader.max_depth = depth;
+    }
+    let shade = |ray: &Ray, hit: Option<(usize, &Hit)>| shader.shade(&renderer, ray, hit);
+    if progressive {
+        // previews are written over the output, the first error is reported
+        let budget = opts.time.map(Duration::from_secs_f64);
+        let mut saved = Ok(());
+        let img = renderer.render_progressive(shade, budget, |img, p| {
+            eprintln!(
+                "pass {}: {} spp, {:.1}s",
+                p.pass,
+                p.samples,
+                p.elapsed.as_secs_f64()
+            );
+            if saved.is_ok() {
+                saved = img.save(&opts.output);
+            }
+        });
+        return saved
+            .and(img.save(&opts.output))
+            .map_err(|e| format!("cannot write {}: {e}", opts.output));
+    }
+
+    let img = renderer.render(shade);
+    img.save(&opts.output)
+        .map_err(|e| format!("cannot write {}: {e}", opts.output))
+}
+
+fn main() -> ExitCode {
+    let opts = match parse_args(std::env::args().skip(1)) {
+        Ok(Some(opts)) => opts,
+        Ok(None) => {
+            println!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        Err(e) => {
+            eprintln!("cg: {e}\n\n{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    match run(&opts) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("cg: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(s: &str) -> impl Iterator<Item = String> + '_ {
+        s.split_whitespace().map(|s| s.to_owned())
+    }
+
+    #[test]
+    fn args_1() {
+        let opts = parse_args(args(
+            "-W 64 --height 48 -a 60 -s 4 -p sobol -f tent -r 1.5 -c 0,0,8,4 -d 0 -j 3 -t 2.5 -o x.ppm a.scene",
+        ))
+        .unwrap()
+        .unwrap();
+        assert_eq!(
+            opts,
+            Options {
+                scene: "a.scene".to_owned(),
+                output: "x.ppm".to_owned(),
+                width: Some(64),
+                height: Some(48),
+                angle: Some(60.),
+                spp: Some(4),
+                pattern: Some(Pattern::Sobol),
+                filter: Some(Filter::Tent),
+                radius: Some(1.5),
+                crop: Some(Window::new(0, 0, 8, 4)),
+                depth: Some(0),
+                threads: Some(3),
+                progressive: false,
+                time: Some(2.5),
+            }
+        );
+    }
+
+    #[test]
+    fn args_2() {
+        assert!(parse_args(args("--help")).unwrap().is_none());
+        assert!(parse_args(args("-W 0 a.scene")).is_err());
+        assert!(parse_args(args("-W 70000 -H 70000 a.scene")).is_err());
+        assert!(parse_args(args("-W 4294967295 a.scene")).is_err());
+        assert!(parse_args(args("-W 8192 -H 8192 a.scene")).is_ok());
+        assert!(parse_args(args("-a 200 a.scene")).is_err());
+        assert!(parse_args(args("-W")).is_err());
+        assert!(parse_args(args("a.scene b.scene")).is_err());
+        assert!(parse_args(args("-x a.scene")).is_err());
+        assert!(parse_args(args("-p poisson a.scene")).is_err());
+        assert!(parse_args(args("-f sinc a.scene")).is_err());
+        assert!(parse_args(args("-r 0 a.scene")).is_err());
+        assert!(parse_args(args("-r inf a.scene")).is_err());
+        assert!(parse_args(args("-r NaN a.scene")).is_err());
+        assert!(parse_args(args("-r 1e9 a.scene")).is_err());
+        assert!(parse_args(args("-r 16 a.scene")).is_ok());
+        assert!(parse_args(args("-c 0,0,8 a.scene")).is_err());
+        assert!(parse_args(args("-c 4,0,2,2 a.scene")).is_err());
+        assert!(parse_args(args("-j 0 a.scene")).is_err());
+        assert!(parse_args(args("-t 0 a.scene")).is_err());
+        assert!(parse_args(args("-P a.scene")).unwrap().unwrap().progressive);
+        assert!(parse_args(args("-P -p regular a.scene")).is_err());
+        assert!(parse_args(args("-t 1 -p jittered a.scene")).is_err());
+        assert!(parse_args(args("-t 1 -p halton a.scene")).is_ok());
+        assert!(parse_args(args("")).is_err());
+    }
+}
@@ -0,0 +1,41 @@
+use crate::{Color, WHITE};
+
+// Blinn-Phong material: ambient, diffuse and specular coefficients are
+// applied to color (ambient, diffuse) and to light color (specular).
+// reflection is the mirror coefficient, transparency the part of light going
+// through the surface (before Fresnel weighting) and ior the index of refraction.
+#[derive(Clone, Debug)]
+pub struct Material {
+    pub color: Color,
+    pub ambient: f64,
+    pub diffuse: f64,
+    pub specular: f64,
+    pub shininess: f64,
+    pub reflection: f64,
+    pub transparency: f64,
+    pub ior: f64,
+}
+
+impl Default for Material {
+    fn default() -> Self {
+        Material {
+            color: WHITE,
+            ambient: 0.1,
+            diffuse: 0.9,
+            specular: 0.3,
+            shininess: 32.,
+            reflection: 0.,
+            transparency: 0.,
+            ior: 1.,
+        }
+    }
+}
+
+impl Material {
+    pub fn new(color: Color) -> Material {
+        Material {
+            color,
+            ..Material::default()
+        }
+    }
+}
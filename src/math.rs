@@ -1,18 +1,24 @@
 mod angle;
 mod consts;
 mod cs;
+mod intervals;
 mod matrix;
 mod nearly;
 mod point;
 mod quad;
+mod quaternion;
+mod roots;
 mod sphcoord;
 mod vector;
 
 pub use angle::{deg_to_rad, rad_to_deg};
 pub use consts::*;
 pub use cs::Cs;
-pub use matrix::Matrix;
+pub use intervals::{Bound, Interval, IntervalList};
+pub use matrix::{EulerOrder, Matrix};
 pub use nearly::{nearly_equal, nearly_zero};
 pub use point::Point;
+pub use quaternion::{Quaternion, ID_QUATERNION};
+pub use roots::{solve_cubic, solve_quadratic, solve_quartic};
 pub use sphcoord::SphCoord;
 pub use vector::Vector;
@@ -1,5 +1,5 @@
 use super::quad::Quad;
-use super::{nearly_equal, Matrix, Point, Vector};
+use super::{nearly_equal, EulerOrder, Matrix, Point, Quaternion, Vector};
 use super::{I, ID_MATRIX, J, K};
 
 pub struct Cs {
@@ -39,6 +39,26 @@ impl Cs {
         self.rcs_to_lcs = mat2;
     }
 
+    pub fn scale_xyz(&mut self, sx: f64, sy: f64, sz: f64) {
+        let mat1 = Matrix::scaling_xyz(sx, sy, sz) * &self.lcs_to_rcs;
+        let mat2 = &self.rcs_to_lcs * Matrix::scaling_xyz(1. / sx, 1. / sy, 1. / sz);
+        self.lcs_to_rcs = mat1;
+        self.rcs_to_lcs = mat2;
+    }
+
+    // see Matrix::shearing, fails if the shear is not invertible
+    pub fn shear(
+        &mut self,
+        xy: f64,
+        xz: f64,
+        yx: f64,
+        yz: f64,
+        zx: f64,
+        zy: f64,
+    ) -> Result<(), &'static str> {
+        self.transform(&Matrix::shearing(xy, xz, yx, yz, zx, zy))
+    }
+
     pub fn translate(&mut self, v: &Vector) {
         let mat1 = Matrix::translation(v) * &self.lcs_to_rcs;
         let mat2 = &self.rcs_to_lcs * Matrix::translation(&(-v));
@@ -67,19 +87,55 @@ impl Cs {
         self.rcs_to_lcs = mat2;
     }
 
-    pub fn complete_cs(&mut self, o: &Point, k: &Vector) {
-        assert!(nearly_equal(k.length(), 1.));
+    pub fn rotate_axis(&mut self, axis: &Vector, deg: f64) {
+        let mat1 = Matrix::rotation_axis(axis, deg) * &self.lcs_to_rcs;
+        let mat2 = &self.rcs_to_lcs * Matrix::rotation_axis(axis, -deg);
+        self.lcs_to_rcs = mat1;
+        self.rcs_to_lcs = mat2;
+    }
+
+    pub fn rotate_euler(&mut self, x_deg: f64, y_deg: f64, z_deg: f64, order: EulerOrder) {
+        let mat1 = Matrix::rotation_euler(x_deg, y_deg, z_deg, order) * &self.lcs_to_rcs;
+        let mat2 =
+            &self.rcs_to_lcs * Matrix::rotation_euler(-x_deg, -y_deg, -z_deg, order.reverse());
+        self.lcs_to_rcs = mat1;
+        self.rcs_to_lcs = mat2;
+    }
+
+    // inverse rotation is the one of the conjugate quaternion
+    pub fn rotate_quaternion(&mut self, q: &Quaternion) {
+        let mat1 = q.to_matrix() * &self.lcs_to_rcs;
+        let mat2 = &self.rcs_to_lcs * q.conjugate().to_matrix();
+        self.lcs_to_rcs = mat1;
+        self.rcs_to_lcs = mat2;
+    }
+
+    // apply any invertible transform m (expressed in reference cs)
+    pub fn transform(&mut self, m: &Matrix) -> Result<(), &'static str> {
+        let inv = m.inverse().ok_or("singular transform matrix")?;
+        let mat1 = m * &self.lcs_to_rcs;
+        let mat2 = &self.rcs_to_lcs * inv;
+        self.lcs_to_rcs = mat1;
+        self.rcs_to_lcs = mat2;
+        Ok(())
+    }
+
+    // cs at o whose k axis is the unit vector k
+    pub fn complete_cs(&mut self, o: &Point, k: &Vector) -> Result<(), &'static str> {
+        if !nearly_equal(k.length(), 1.) {
+            return Err("local cs axis is not a unit vector");
+        }
 
         if k.nearly_equal(&J) {
-            self.set_lcs(o, &-I, &K, &J).unwrap();
+            self.set_lcs(o, &-I, &K, &J)?;
         } else if k.nearly_equal(&(-1. * &J)) {
-            self.set_lcs(o, &-K, &I, &-J).unwrap();
+            self.set_lcs(o, &-K, &I, &-J)?;
         } else {
             let j = (&J - (k.y * k)).unit();
             let i = &j ^ k;
-            self.set_lcs(o, &i, &j, k).unwrap();
+            self.set_lcs(o, &i, &j, k)?;
         }
-        self.compute_reverse_base();
+        self.compute_reverse_base()
     }
 
     fn set_lcs(
@@ -97,19 +153,12 @@ impl Cs {
         Ok(())
     }
 
-    fn compute_reverse_base(&mut self) {
-        // FIXME: Works only for unit vectors !
-        let i = self.lcs_to_rcs.get_i();
-        let j = self.lcs_to_rcs.get_j();
-        let k = self.lcs_to_rcs.get_k();
-        let o = self.lcs_to_rcs.get_o();
-
-        self.rcs_to_lcs = Matrix::from_lines(
-            [i.x, i.y, i.z, -i.x * o.x - i.y * o.y - i.z * o.z],
-            [j.x, j.y, j.z, -j.x * o.x - j.y * o.y - j.z * o.z],
-            [k.x, k.y, k.z, -k.x * o.x - k.y * o.y - k.z * o.z],
-            [0., 0., 0., 1.],
-        );
+    fn compute_reverse_base(&mut self) -> Result<(), &'static str> {
+        self.rcs_to_lcs = self
+            .lcs_to_rcs
+            .affine_inverse()
+            .ok_or("local cs base is not invertible")?;
+        Ok(())
     }
 }
 
@@ -159,4 +208,52 @@ mod tests {
             panic!("{e}");
         }
     }
+
+    #[test]
+    fn transform_1() {
+        let shear = Matrix::from_lines(
+            [1., 0.7, 0., 0.],
+            [0., 1., 0., 0.],
+            [0.2, 0., 3., 0.],
+            [0., 0., 0., 1.],
+        );
+        let mut cs = Cs::new();
+        cs.rotate_z(30.);
+        cs.transform(&shear).unwrap();
+        cs.translate(&Vector::new(1., 2., 3.));
+
+        let m = cs.get_matrix_to_rcs() * cs.get_matrix_to_lcs();
+        assert!(m.nearly_equal(&ID_MATRIX));
+        assert!(cs.transform(&Matrix::scaling(0.)).is_err());
+    }
+
+    #[test]
+    fn reverse_base_1() {
+        let mut cs = Cs::new();
+        cs.complete_cs(&Point::new(1., 2., 3.), &Vector::new(3., -1., 2.).unit())
+            .unwrap();
+
+        let m = cs.get_matrix_to_lcs() * cs.get_matrix_to_rcs();
+        assert!(m.nearly_equal(&ID_MATRIX));
+
+        // far from the origin
+        let o = Point::new(2e4, 2e4, 2e4);
+        cs.complete_cs(&o, &Vector::new(-1., -1., -1.).unit())
+            .unwrap();
+        assert!(cs.complete_cs(&o, &Vector::new(0., 0., 0.)).is_err());
+    }
+
+    #[test]
+    fn inverses_1() {
+        let mut cs = Cs::new();
+        cs.scale_xyz(1., 2., 0.5);
+        cs.rotate_axis(&Vector::new(1., -1., 2.), 25.);
+        cs.rotate_euler(10., 20., 30., EulerOrder::ZXY);
+        cs.rotate_quaternion(&Quaternion::new(0.3, 0.1, -0.5, 0.2));
+        cs.shear(0.2, 0., 0.5, 0., 0., 1.).unwrap();
+
+        let m = cs.get_matrix_to_rcs() * cs.get_matrix_to_lcs();
+        assert!(m.nearly_equal(&ID_MATRIX));
+        assert!(cs.shear(1., 0., 1., 0., 0., 0.).is_err());
+    }
 }
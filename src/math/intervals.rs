@@ -1,6 +1,7 @@
 use std::cmp::Ordering;
 use std::fmt::Display;
 
+#[derive(Clone, Copy, Debug)]
 pub enum Bound {
     Open(f64),
     Closed(f64),
@@ -58,6 +59,7 @@ impl PartialOrd for Bound {
     }
 }
 
+#[derive(Clone, Copy, Debug)]
 pub struct Interval {
     inf: Bound,
     sup: Bound,
@@ -110,10 +112,255 @@ impl Interval {
         }
     }
 
+    pub fn inf(&self) -> &Bound {
+        &self.inf
+    }
+
+    pub fn sup(&self) -> &Bound {
+        &self.sup
+    }
+
     pub fn is_empty(&self) -> bool {
-        match (&self.inf, &self.sup) {
-            (Bound::Open(k1), Bound::Open(k2)) => k1 == k2,
-            _ => false,
+        let (k1, k2) = (self.inf.unwrap(), self.sup.unwrap());
+        k1 > k2 || (k1 == k2 && !self.is_singleton())
+    }
+
+    pub fn contains(&self, k: f64) -> bool {
+        let above_inf = match self.inf {
+            Bound::Closed(k1) => k >= k1,
+            Bound::Open(k1) => k > k1,
+            Bound::NegativeInfinity => true,
+            Bound::PositiveInfinity => false,
+        };
+        let below_sup = match self.sup {
+            Bound::Closed(k2) => k <= k2,
+            Bound::Open(k2) => k < k2,
+            Bound::NegativeInfinity => false,
+            Bound::PositiveInfinity => true,
+        };
+        above_inf && below_sup
+    }
+
+    // true if other is a subset of self
+    pub fn includes(&self, other: &Interval) -> bool {
+        other.is_empty()
+            || (inf_key(&self.inf) <= inf_key(&other.inf)
+                && sup_key(&other.sup) <= sup_key(&self.sup))
+    }
+
+    pub fn intersection(&self, other: &Interval) -> Interval {
+        let inf = if inf_key(&self.inf) >= inf_key(&other.inf) {
+            self.inf
+        } else {
+            other.inf
+        };
+        let sup = if sup_key(&self.sup) <= sup_key(&other.sup) {
+            self.sup
+        } else {
+            other.sup
+        };
+
+        let i = Interval { inf, sup };
+        if i.is_empty() {
+            EMPTY
+        } else {
+            i
+        }
+    }
+
+    pub fn union(&self, other: &Interval) -> Vec<Interval> {
+        IntervalList::from(vec![*self, *other]).into_vec()
+    }
+
+    pub fn difference(&self, other: &Interval) -> Vec<Interval> {
+        if self.is_empty() {
+            return vec![];
+        }
+        if other.is_empty() {
+            return vec![*self];
+        }
+
+        // self ∩ complement(other), complement being made of at most two pieces
+        let before = match other.inf {
+            Bound::Closed(k) => Some(Bound::Open(k)),
+            Bound::Open(k) => Some(Bound::Closed(k)),
+            Bound::NegativeInfinity => None,
+            Bound::PositiveInfinity => Some(Bound::PositiveInfinity),
+        };
+        let after = match other.sup {
+            Bound::Closed(k) => Some(Bound::Open(k)),
+            Bound::Open(k) => Some(Bound::Closed(k)),
+            Bound::NegativeInfinity => Some(Bound::NegativeInfinity),
+            Bound::PositiveInfinity => None,
+        };
+
+        let mut result = vec![];
+        if let Some(sup) = before {
+            result.push(self.intersection(&Interval {
+                inf: Bound::NegativeInfinity,
+                sup,
+            }));
+        }
+        if let Some(inf) = after {
+            result.push(self.intersection(&Interval {
+                inf,
+                sup: Bound::PositiveInfinity,
+            }));
+        }
+        result.retain(|i| !i.is_empty());
+        result
+    }
+
+    // intersection with [t_min, t_max]
+    pub fn clip(&self, t_min: f64, t_max: f64) -> Interval {
+        self.intersection(&Interval {
+            inf: Bound::Closed(t_min),
+            sup: Bound::Closed(t_max),
+        })
+    }
+
+    // true if self and other overlap or touch (e.g. [a,k) and [k,b])
+    fn is_connected_to(&self, other: &Interval) -> bool {
+        !is_gap(&self.sup, &other.inf) && !is_gap(&other.sup, &self.inf)
+    }
+}
+
+// Bounds ordering as lower bounds: at the same value, closed comes first
+fn inf_key(b: &Bound) -> (f64, bool) {
+    match b {
+        Bound::Closed(k) => (*k, false),
+        Bound::Open(k) => (*k, true),
+        Bound::NegativeInfinity => (f64::NEG_INFINITY, false),
+        Bound::PositiveInfinity => (f64::INFINITY, true),
+    }
+}
+
+// Bounds ordering as upper bounds: at the same value, open comes first
+fn sup_key(b: &Bound) -> (f64, bool) {
+    match b {
+        Bound::Closed(k) => (*k, true),
+        Bound::Open(k) => (*k, false),
+        Bound::NegativeInfinity => (f64::NEG_INFINITY, false),
+        Bound::PositiveInfinity => (f64::INFINITY, true),
+    }
+}
+
+// true if some values lie strictly between upper bound sup and lower bound inf
+fn is_gap(sup: &Bound, inf: &Bound) -> bool {
+    let (k1, k2) = (sup.unwrap(), inf.unwrap());
+    k1 < k2 || (k1 == k2 && matches!((sup, inf), (Bound::Open(_), Bound::Open(_))))
+}
+
+// Sorted list of disjoint, non empty and non touching intervals
+#[derive(Clone, Debug, Default)]
+pub struct IntervalList {
+    intervals: Vec<Interval>,
+}
+
+impl From<Vec<Interval>> for IntervalList {
+    fn from(mut intervals: Vec<Interval>) -> Self {
+        intervals.retain(|i| !i.is_empty());
+        intervals.sort_by(|a, b| {
+            let (k1, o1) = inf_key(&a.inf);
+            let (k2, o2) = inf_key(&b.inf);
+            k1.total_cmp(&k2).then(o1.cmp(&o2))
+        });
+
+        let mut merged: Vec<Interval> = Vec::with_capacity(intervals.len());
+        for i in intervals {
+            match merged.last_mut() {
+                Some(last) if last.is_connected_to(&i) => {
+                    if sup_key(&i.sup) > sup_key(&last.sup) {
+                        last.sup = i.sup;
+                    }
+                }
+                _ => merged.push(i),
+            }
+        }
+        IntervalList { intervals: merged }
+    }
+}
+
+impl From<Interval> for IntervalList {
+    fn from(i: Interval) -> Self {
+        IntervalList::from(vec![i])
+    }
+}
+
+impl IntervalList {
+    pub fn new() -> IntervalList {
+        IntervalList::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.intervals.len()
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, Interval> {
+        self.intervals.iter()
+    }
+
+    pub fn as_slice(&self) -> &[Interval] {
+        &self.intervals
+    }
+
+    pub fn into_vec(self) -> Vec<Interval> {
+        self.intervals
+    }
+
+    pub fn contains(&self, k: f64) -> bool {
+        self.intervals.iter().any(|i| i.contains(k))
+    }
+
+    pub fn includes(&self, other: &Interval) -> bool {
+        other.is_empty() || self.intervals.iter().any(|i| i.includes(other))
+    }
+
+    pub fn union(&self, other: &IntervalList) -> IntervalList {
+        let mut all = self.intervals.clone();
+        all.extend_from_slice(&other.intervals);
+        IntervalList::from(all)
+    }
+
+    pub fn intersection(&self, other: &IntervalList) -> IntervalList {
+        let mut result = vec![];
+        for a in &self.intervals {
+            for b in &other.intervals {
+                result.push(a.intersection(b));
+            }
+        }
+        IntervalList::from(result)
+    }
+
+    pub fn difference(&self, other: &IntervalList) -> IntervalList {
+        let mut result = self.intervals.clone();
+        for b in &other.intervals {
+            result = result.iter().flat_map(|a| a.difference(b)).collect();
+        }
+        IntervalList::from(result)
+    }
+
+    pub fn clip(&self, t_min: f64, t_max: f64) -> IntervalList {
+        IntervalList::from(
+            self.intervals
+                .iter()
+                .map(|i| i.clip(t_min, t_max))
+                .collect::<Vec<Interval>>(),
+        )
+    }
+}
+
+impl Display for IntervalList {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_empty() {
+            write!(f, "∅")
+        } else {
+            let strs: Vec<String> = self.intervals.iter().map(|i| format!("{i}")).collect();
+            write!(f, "{}", strs.join(" ∪ "))
         }
     }
 }
@@ -212,4 +459,90 @@ mod test {
         let a = Interval::new(Bound::Open(42.), Bound::Closed(-5.));
         assert_eq!(format!("{a}"), "[ -5.00, 42.00)");
     }
+
+    fn closed(k1: f64, k2: f64) -> Interval {
+        Interval::new(Bound::Closed(k1), Bound::Closed(k2))
+    }
+
+    fn open(k1: f64, k2: f64) -> Interval {
+        Interval::new(Bound::Open(k1), Bound::Open(k2))
+    }
+
+    #[test]
+    fn test_empty() {
+        assert!(Interval::new(Bound::Closed(1.), Bound::Open(1.)).is_empty());
+        assert!(!Interval::singleton(1.).is_empty());
+        assert!(closed(0., 1.).clip(2., 3.).is_empty());
+    }
+
+    #[test]
+    fn test_contains() {
+        assert!(closed(1., 2.).contains(1.));
+        assert!(!open(1., 2.).contains(1.));
+        assert!(open(1., 2.).contains(1.5));
+        assert!(Interval::new(Bound::NegativeInfinity, Bound::Open(0.)).contains(-1e300));
+        assert!(closed(0., 10.).includes(&open(0., 10.)));
+        assert!(!open(0., 10.).includes(&closed(0., 10.)));
+    }
+
+    #[test]
+    fn test_intersection() {
+        assert_eq!(
+            format!("{}", closed(0., 2.).intersection(&open(1., 3.))),
+            "(  1.00,  2.00]"
+        );
+        assert_eq!(
+            format!("{}", closed(0., 1.).intersection(&closed(1., 3.))),
+            "{  1.00}"
+        );
+        assert!(closed(0., 1.).intersection(&open(1., 3.)).is_empty());
+    }
+
+    #[test]
+    fn test_union() {
+        let u = Interval::new(Bound::Closed(0.), Bound::Open(1.)).union(&closed(1., 2.));
+        assert_eq!(u.len(), 1);
+        assert_eq!(format!("{}", u[0]), "[  0.00,  2.00]");
+
+        let u = Interval::new(Bound::Closed(0.), Bound::Open(1.)).union(&open(1., 2.));
+        assert_eq!(u.len(), 2);
+
+        let u = closed(3., 4.).union(&closed(0., 1.));
+        assert_eq!(
+            format!("{}", IntervalList::from(u)),
+            "[  0.00,  1.00] ∪ [  3.00,  4.00]"
+        );
+    }
+
+    #[test]
+    fn test_difference() {
+        let d = closed(0., 10.).difference(&closed(2., 3.));
+        assert_eq!(
+            format!("{}", IntervalList::from(d)),
+            "[  0.00,  2.00) ∪ (  3.00, 10.00]"
+        );
+
+        let d = closed(0., 10.).difference(&open(0., 10.));
+        assert_eq!(format!("{}", IntervalList::from(d)), "{  0.00} ∪ { 10.00}");
+
+        assert!(closed(2., 3.).difference(&closed(0., 10.)).is_empty());
+    }
+
+    #[test]
+    fn test_list() {
+        let a = IntervalList::from(vec![closed(0., 2.), closed(4., 6.)]);
+        let b = IntervalList::from(vec![closed(1., 5.)]);
+
+        assert_eq!(format!("{}", a.union(&b)), "[  0.00,  6.00]");
+        assert_eq!(
+            format!("{}", a.intersection(&b)),
+            "[  1.00,  2.00] ∪ [  4.00,  5.00]"
+        );
+        assert_eq!(
+            format!("{}", a.difference(&b)),
+            "[  0.00,  1.00) ∪ (  5.00,  6.00]"
+        );
+        assert_eq!(format!("{}", a.clip(1., 4.)), "[  1.00,  2.00] ∪ {  4.00}");
+        assert!(a.contains(5.) && !a.contains(3.));
+    }
 }
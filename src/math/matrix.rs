@@ -1,8 +1,40 @@
 use auto_ops::impl_op_ex;
 use std::fmt;
 
-use super::{deg_to_rad, Point, Vector, ID_MATRIX};
+use super::{deg_to_rad, nearly_equal, Point, Vector, ID_MATRIX};
 
+// below this ratio of |det| to its Hadamard bound, a matrix is singular
+const SINGULAR_TH: f64 = 1e-12;
+
+fn is_singular(det: f64, bound: f64) -> bool {
+    !det.is_finite() || bound == 0. || det.abs() <= SINGULAR_TH * bound
+}
+
+// Order in which rotations around X, Y and Z axes are applied
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum EulerOrder {
+    XYZ,
+    XZY,
+    YXZ,
+    YZX,
+    ZXY,
+    ZYX,
+}
+
+impl EulerOrder {
+    pub fn reverse(&self) -> EulerOrder {
+        match self {
+            EulerOrder::XYZ => EulerOrder::ZYX,
+            EulerOrder::XZY => EulerOrder::YZX,
+            EulerOrder::YXZ => EulerOrder::ZXY,
+            EulerOrder::YZX => EulerOrder::XZY,
+            EulerOrder::ZXY => EulerOrder::YXZ,
+            EulerOrder::ZYX => EulerOrder::XYZ,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
 pub struct Matrix {
     pub m: [[f64; 4]; 4],
 }
@@ -57,6 +89,143 @@ impl Matrix {
         }
     }
 
+    pub fn nearly_equal(&self, m: &Matrix) -> bool {
+        (0..4).all(|l| (0..4).all(|c| nearly_equal(self.m[l][c], m.m[l][c])))
+    }
+
+    pub fn is_affine(&self) -> bool {
+        self.m[3] == [0., 0., 0., 1.]
+    }
+
+    pub fn determinant(&self) -> f64 {
+        let (s, c) = self.minors_2x2();
+
+        s[0] * c[5] - s[1] * c[4] + s[2] * c[3] + s[3] * c[2] - s[4] * c[1] + s[5] * c[0]
+    }
+
+    // general inverse (Laplace expansion), None if the matrix is singular.
+    // Affine matrices go through affine_inverse: their translation must not
+    // weigh in the singularity test.
+    pub fn inverse(&self) -> Option<Matrix> {
+        if self.is_affine() {
+            return self.affine_inverse();
+        }
+        let m = &self.m;
+        let (s, c) = self.minors_2x2();
+
+        let det = s[0] * c[5] - s[1] * c[4] + s[2] * c[3] + s[3] * c[2] - s[4] * c[1] + s[5] * c[0];
+        // Hadamard bound: product of the lines norms
+        let bound: f64 = m
+            .iter()
+            .map(|l| l.iter().map(|k| k * k).sum::<f64>().sqrt())
+            .product();
+        if is_singular(det, bound) {
+            return None;
+        }
+        let inv = 1. / det;
+
+        Some(Matrix {
+            m: [
+                [
+                    (m[1][1] * c[5] - m[1][2] * c[4] + m[1][3] * c[3]) * inv,
+                    (-m[0][1] * c[5] + m[0][2] * c[4] - m[0][3] * c[3]) * inv,
+                    (m[3][1] * s[5] - m[3][2] * s[4] + m[3][3] * s[3]) * inv,
+                    (-m[2][1] * s[5] + m[2][2] * s[4] - m[2][3] * s[3]) * inv,
+                ],
+                [
+                    (-m[1][0] * c[5] + m[1][2] * c[2] - m[1][3] * c[1]) * inv,
+                    (m[0][0] * c[5] - m[0][2] * c[2] + m[0][3] * c[1]) * inv,
+                    (-m[3][0] * s[5] + m[3][2] * s[2] - m[3][3] * s[1]) * inv,
+                    (m[2][0] * s[5] - m[2][2] * s[2] + m[2][3] * s[1]) * inv,
+                ],
+                [
+                    (m[1][0] * c[4] - m[1][1] * c[2] + m[1][3] * c[0]) * inv,
+                    (-m[0][0] * c[4] + m[0][1] * c[2] - m[0][3] * c[0]) * inv,
+                    (m[3][0] * s[4] - m[3][1] * s[2] + m[3][3] * s[0]) * inv,
+                    (-m[2][0] * s[4] + m[2][1] * s[2] - m[2][3] * s[0]) * inv,
+                ],
+                [
+                    (-m[1][0] * c[3] + m[1][1] * c[1] - m[1][2] * c[0]) * inv,
+                    (m[0][0] * c[3] - m[0][1] * c[1] + m[0][2] * c[0]) * inv,
+                    (-m[3][0] * s[3] + m[3][1] * s[1] - m[3][2] * s[0]) * inv,
+                    (m[2][0] * s[3] - m[2][1] * s[1] + m[2][2] * s[0]) * inv,
+                ],
+            ],
+        })
+    }
+
+    // inverse of a matrix whose last line is [0 0 0 1]: the 3x3 linear part
+    // is inverted and the translation is -A⁻¹t. None if singular or not affine.
+    pub fn affine_inverse(&self) -> Option<Matrix> {
+        if !self.is_affine() {
+            return None;
+        }
+        let m = &self.m;
+
+        let c00 = m[1][1] * m[2][2] - m[1][2] * m[2][1];
+        let c01 = m[1][2] * m[2][0] - m[1][0] * m[2][2];
+        let c02 = m[1][0] * m[2][1] - m[1][1] * m[2][0];
+        let det = m[0][0] * c00 + m[0][1] * c01 + m[0][2] * c02;
+        // product of the columns norms of the linear part only
+        let bound: f64 = (0..3)
+            .map(|c| (0..3).map(|l| m[l][c] * m[l][c]).sum::<f64>().sqrt())
+            .product();
+        if is_singular(det, bound) {
+            return None;
+        }
+        let inv = 1. / det;
+
+        let a = [
+            [
+                c00 * inv,
+                (m[0][2] * m[2][1] - m[0][1] * m[2][2]) * inv,
+                (m[0][1] * m[1][2] - m[0][2] * m[1][1]) * inv,
+            ],
+            [
+                c01 * inv,
+                (m[0][0] * m[2][2] - m[0][2] * m[2][0]) * inv,
+                (m[0][2] * m[1][0] - m[0][0] * m[1][2]) * inv,
+            ],
+            [
+                c02 * inv,
+                (m[0][1] * m[2][0] - m[0][0] * m[2][1]) * inv,
+                (m[0][0] * m[1][1] - m[0][1] * m[1][0]) * inv,
+            ],
+        ];
+        let t = [m[0][3], m[1][3], m[2][3]];
+        let tx = |l: usize| -(a[l][0] * t[0] + a[l][1] * t[1] + a[l][2] * t[2]);
+
+        Some(Matrix::from_lines(
+            [a[0][0], a[0][1], a[0][2], tx(0)],
+            [a[1][0], a[1][1], a[1][2], tx(1)],
+            [a[2][0], a[2][1], a[2][2], tx(2)],
+            [0., 0., 0., 1.],
+        ))
+    }
+
+    // 2x2 minors of the two top lines (s) and of the two bottom lines (c)
+    fn minors_2x2(&self) -> ([f64; 6], [f64; 6]) {
+        let m = &self.m;
+        (
+            [
+                m[0][0] * m[1][1] - m[1][0] * m[0][1],
+                m[0][0] * m[1][2] - m[1][0] * m[0][2],
+                m[0][0] * m[1][3] - m[1][0] * m[0][3],
+                m[0][1] * m[1][2] - m[1][1] * m[0][2],
+                m[0][1] * m[1][3] - m[1][1] * m[0][3],
+                m[0][2] * m[1][3] - m[1][2] * m[0][3],
+            ],
+            [
+                m[2][0] * m[3][1] - m[3][0] * m[2][1],
+                m[2][0] * m[3][2] - m[3][0] * m[2][2],
+                m[2][0] * m[3][3] - m[3][0] * m[2][3],
+                m[2][1] * m[3][2] - m[3][1] * m[2][2],
+                m[2][1] * m[3][3] - m[3][1] * m[2][3],
+                m[2][2] * m[3][3] - m[3][2] * m[2][3],
+            ],
+        )
+    }
+
     pub fn translation(v: &Vector) -> Matrix {
         Matrix {
             m: [
@@ -79,6 +248,109 @@ impl Matrix {
         }
     }
 
+    pub fn scaling_xyz(sx: f64, sy: f64, sz: f64) -> Matrix {
+        Matrix {
+            m: [
+                [sx, 0., 0., 0.],
+                [0., sy, 0., 0.],
+                [0., 0., sz, 0.],
+                [0., 0., 0., 1.],
+            ],
+        }
+    }
+
+    // x' = x + xy * y + xz * z, y' = yx * x + y + yz * z, z' = zx * x + zy * y + z
+    pub fn shearing(xy: f64, xz: f64, yx: f64, yz: f64, zx: f64, zy: f64) -> Matrix {
+        Matrix {
+            m: [
+                [1., xy, xz, 0.],
+                [yx, 1., yz, 0.],
+                [zx, zy, 1., 0.],
+                [0., 0., 0., 1.],
+            ],
+        }
+    }
+
+    // rotation of deg degrees around axis (Rodrigues formula)
+    pub fn rotation_axis(axis: &Vector, deg: f64) -> Matrix {
+        let u = axis.clone().unit();
+        let rad = deg_to_rad(deg);
+        let (sin, cos) = rad.sin_cos();
+        let t = 1. - cos;
+
+        Matrix {
+            m: [
+                [
+                    cos + u.x * u.x * t,
+                    u.x * u.y * t - u.z * sin,
+                    u.x * u.z * t + u.y * sin,
+                    0.,
+                ],
+                [
+                    u.y * u.x * t + u.z * sin,
+                    cos + u.y * u.y * t,
+                    u.y * u.z * t - u.x * sin,
+                    0.,
+                ],
+                [
+                    u.z * u.x * t - u.y * sin,
+                    u.z * u.y * t + u.x * sin,
+                    cos + u.z * u.z * t,
+                    0.,
+                ],
+                [0., 0., 0., 1.],
+            ],
+        }
+    }
+
+    // rotation of unit quaternion w + xi + yj + zk (normalized here)
+    pub fn from_quaternion(w: f64, x: f64, y: f64, z: f64) -> Matrix {
+        let n = (w * w + x * x + y * y + z * z).sqrt();
+        let (w, x, y, z) = (w / n, x / n, y / n, z / n);
+
+        Matrix {
+            m: [
+                [
+                    1. - 2. * (y * y + z * z),
+                    2. * (x * y - w * z),
+                    2. * (x * z + w * y),
+                    0.,
+                ],
+                [
+                    2. * (x * y + w * z),
+                    1. - 2. * (x * x + z * z),
+                    2. * (y * z - w * x),
+                    0.,
+                ],
+                [
+                    2. * (x * z - w * y),
+                    2. * (y * z + w * x),
+                    1. - 2. * (x * x + y * y),
+                    0.,
+                ],
+                [0., 0., 0., 1.],
+            ],
+        }
+    }
+
+    // rotations around fixed X, Y and Z axes, applied in the given order
+    pub fn rotation_euler(x_deg: f64, y_deg: f64, z_deg: f64, order: EulerOrder) -> Matrix {
+        let (rx, ry, rz) = (
+            Matrix::rotation_x(x_deg),
+            Matrix::rotation_y(y_deg),
+            Matrix::rotation_z(z_deg),
+        );
+
+        match order {
+            EulerOrder::XYZ => rz * ry * rx,
+            EulerOrder::XZY => ry * rz * rx,
+            EulerOrder::YXZ => rz * rx * ry,
+            EulerOrder::YZX => rx * rz * ry,
+            EulerOrder::ZXY => ry * rx * rz,
+            EulerOrder::ZYX => rx * ry * rz,
+        }
+    }
+
     pub fn rotation_x(deg: f64) -> Matrix {
         let rad = deg_to_rad(deg);
 
@@ -238,3 +510,116 @@ impl_op_ex!(*|lhs: &Matrix, rhs: &Matrix| -> Matrix {
         ],
     }
 });
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{nearly_equal, nearly_zero};
+
+    fn any_matrix() -> Matrix {
+        Matrix::from_lines(
+            [2., -1., 0., 3.],
+            [1., 4., 2., -2.],
+            [0., 1., 5., 1.],
+            [1., 0., -1., 2.],
+        )
+    }
+
+    #[test]
+    fn determinant_1() {
+        assert!(nearly_equal(ID_MATRIX.determinant(), 1.));
+        assert!(nearly_equal(Matrix::scaling(2.).determinant(), 8.));
+        assert!(nearly_equal(any_matrix().determinant(), 56.));
+        assert!(nearly_equal(Matrix::rotation_x(33.).determinant(), 1.));
+    }
+
+    #[test]
+    fn inverse_1() {
+        let m = any_matrix();
+        let inv = m.inverse().unwrap();
+        assert!((&m * &inv).nearly_equal(&ID_MATRIX));
+        assert!((&inv * &m).nearly_equal(&ID_MATRIX));
+    }
+
+    #[test]
+    fn inverse_2() {
+        let m = Matrix::from_lines(
+            [1., 2., 3., 4.],
+            [2., 4., 6., 8.],
+            [0., 1., 5., 1.],
+            [1., 0., -1., 2.],
+        );
+        assert!(nearly_zero(m.determinant()));
+        assert!(m.inverse().is_none());
+        assert!(Matrix::scaling(0.).affine_inverse().is_none());
+    }
+
+    #[test]
+    fn affine_inverse_1() {
+        let m = Matrix::translation(&Vector::new(1., -2., 3.))
+            * Matrix::rotation_y(27.)
+            * Matrix::from_lines(
+                [1., 0.5, 0., 0.],
+                [0., 2., 0., 0.],
+                [0., 0., 0.1, 0.],
+                [0., 0., 0., 1.],
+            );
+        let inv = m.affine_inverse().unwrap();
+        assert!((&m * &inv).nearly_equal(&ID_MATRIX));
+        assert!(inv.nearly_equal(&m.inverse().unwrap()));
+        assert!(any_matrix().affine_inverse().is_none());
+    }
+
+    #[test]
+    fn affine_inverse_2() {
+        // a rigid frame far from the origin is well-conditioned
+        let m = Matrix::translation(&Vector::new(2e4, 2e4, 2e4)) * Matrix::rotation_y(27.);
+        let inv = m.affine_inverse().unwrap();
+        assert!((&m * &inv).nearly_equal(&ID_MATRIX));
+        assert!(m.inverse().is_some());
+
+        // whatever the scale
+        assert!(Matrix::scaling(1e-5).affine_inverse().is_some());
+        assert!(Matrix::scaling_xyz(1., 1., 0.).affine_inverse().is_none());
+    }
+
+    #[test]
+    fn rotation_axis_1() {
+        assert!(Matrix::rotation_axis(&Vector::new(2., 0., 0.), 33.)
+            .nearly_equal(&Matrix::rotation_x(33.)));
+        assert!(Matrix::rotation_axis(&Vector::new(0., -1., 0.), 33.)
+            .nearly_equal(&Matrix::rotation_y(-33.)));
+
+        let axis = Vector::new(1., 1., 1.);
+        let m = Matrix::rotation_axis(&axis, 120.);
+        assert!((&m * &Vector::new(1., 0., 0.)).nearly_equal(&Vector::new(0., 1., 0.)));
+        assert!((&m * &axis).nearly_equal(&axis));
+    }
+
+    #[test]
+    fn quaternion_1() {
+        let half = deg_to_rad(40.) / 2.;
+        let m = Matrix::from_quaternion(2. * half.cos(), 0., 0., 2. * half.sin());
+        assert!(m.nearly_equal(&Matrix::rotation_z(40.)));
+    }
+
+    #[test]
+    fn euler_1() {
+        let m = Matrix::rotation_euler(90., 90., 0., EulerOrder::XYZ);
+        assert!((&m * &Vector::new(0., 1., 0.)).nearly_equal(&Vector::new(1., 0., 0.)));
+
+        let m = Matrix::rotation_euler(90., 90., 0., EulerOrder::YXZ);
+        assert!((&m * &Vector::new(0., 1., 0.)).nearly_equal(&Vector::new(0., 0., 1.)));
+
+        let inv = Matrix::rotation_euler(-10., -20., -30., EulerOrder::XZY.reverse());
+        let m = Matrix::rotation_euler(10., 20., 30., EulerOrder::XZY);
+        assert!((m * inv).nearly_equal(&ID_MATRIX));
+    }
+
+    #[test]
+    fn shearing_1() {
+        let m = Matrix::shearing(1., 0., 0., 0., 0., 0.);
+        assert!((&m * &Point::new(2., 3., 4.)).nearly_equal(&Point::new(5., 3., 4.)));
+        assert!(nearly_equal(m.determinant(), 1.));
+    }
+}
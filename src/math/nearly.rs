@@ -1,9 +1,7 @@
 // reference: https://stackoverflow.com/a/32334103/2212464
 
-use std::f64::{MAX, MIN_POSITIVE};
-
 const EPSILON: f64 = 1e-7;
-const ABS_TH: f64 = MIN_POSITIVE;
+const ABS_TH: f64 = f64::MIN_POSITIVE;
 
 pub fn nearly_equal(a: f64, b: f64) -> bool {
     if a == b {
@@ -14,7 +12,7 @@ pub fn nearly_equal(a: f64, b: f64) -> bool {
         nearly_zero(a)
     } else {
         let diff = (a - b).abs();
-        let norm = MAX.min(a.abs() + b.abs());
+        let norm = f64::MAX.min(a.abs() + b.abs());
 
         diff < ABS_TH.max(EPSILON * norm)
     }
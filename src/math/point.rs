@@ -68,6 +68,10 @@ impl_op_ex!(+= |lhs: &mut Point, rhs: &Vector| {
     lhs.z += rhs.z;
 });
 
+impl_op_ex!(-|lhs: &Point, rhs: &Vector| -> Point {
+    Point::new(lhs.x - rhs.x, lhs.y - rhs.y, lhs.z - rhs.z)
+});
+
 impl_op_ex!(-|lhs: &Point, rhs: &Point| -> Vector {
     Vector::new(lhs.x - rhs.x, lhs.y - rhs.y, lhs.z - rhs.z)
 });
@@ -0,0 +1,223 @@
+use auto_ops::impl_op_ex;
+use std::fmt;
+
+use super::{deg_to_rad, nearly_equal, nearly_zero, Matrix, Vector};
+
+// Quaternion w + xi + yj + zk, unit quaternions represent rotations
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Quaternion {
+    pub w: f64,
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+}
+
+pub const ID_QUATERNION: Quaternion = Quaternion::new(1., 0., 0., 0.);
+
+impl Default for Quaternion {
+    fn default() -> Self {
+        ID_QUATERNION
+    }
+}
+
+impl fmt::Display for Quaternion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:.2} + {:.2}i + {:.2}j + {:.2}k",
+            self.w, self.x, self.y, self.z
+        )
+    }
+}
+
+impl Quaternion {
+    pub const fn new(w: f64, x: f64, y: f64, z: f64) -> Quaternion {
+        Quaternion { w, x, y, z }
+    }
+
+    // rotation of deg degrees around axis
+    pub fn from_axis_angle(axis: &Vector, deg: f64) -> Quaternion {
+        let u = axis.clone().unit();
+        let (sin, cos) = (deg_to_rad(deg) / 2.).sin_cos();
+
+        Quaternion::new(cos, sin * u.x, sin * u.y, sin * u.z)
+    }
+
+    // rotation part of m, which must be a rotation matrix (Shepperd method)
+    pub fn from_matrix(m: &Matrix) -> Quaternion {
+        let m = &m.m;
+        let trace = m[0][0] + m[1][1] + m[2][2];
+
+        let q = if trace > 0. {
+            let s = 2. * (trace + 1.).sqrt();
+            Quaternion::new(
+                s / 4.,
+                (m[2][1] - m[1][2]) / s,
+                (m[0][2] - m[2][0]) / s,
+                (m[1][0] - m[0][1]) / s,
+            )
+        } else if m[0][0] > m[1][1] && m[0][0] > m[2][2] {
+            let s = 2. * (1. + m[0][0] - m[1][1] - m[2][2]).sqrt();
+            Quaternion::new(
+                (m[2][1] - m[1][2]) / s,
+                s / 4.,
+                (m[0][1] + m[1][0]) / s,
+                (m[0][2] + m[2][0]) / s,
+            )
+        } else if m[1][1] > m[2][2] {
+            let s = 2. * (1. + m[1][1] - m[0][0] - m[2][2]).sqrt();
+            Quaternion::new(
+                (m[0][2] - m[2][0]) / s,
+                (m[0][1] + m[1][0]) / s,
+                s / 4.,
+                (m[1][2] + m[2][1]) / s,
+            )
+        } else {
+            let s = 2. * (1. + m[2][2] - m[0][0] - m[1][1]).sqrt();
+            Quaternion::new(
+                (m[1][0] - m[0][1]) / s,
+                (m[0][2] + m[2][0]) / s,
+                (m[1][2] + m[2][1]) / s,
+                s / 4.,
+            )
+        };
+        q.unit()
+    }
+
+    pub fn to_matrix(&self) -> Matrix {
+        Matrix::from_quaternion(self.w, self.x, self.y, self.z)
+    }
+
+    pub fn norm(&self) -> f64 {
+        self.dot(self).sqrt()
+    }
+
+    pub fn unit(&self) -> Quaternion {
+        let n = self.norm();
+        if nearly_zero(n) {
+            panic!("zero quaternion!")
+        }
+        Quaternion::new(self.w / n, self.x / n, self.y / n, self.z / n)
+    }
+
+    pub fn conjugate(&self) -> Quaternion {
+        Quaternion::new(self.w, -self.x, -self.y, -self.z)
+    }
+
+    pub fn dot(&self, q: &Quaternion) -> f64 {
+        self.w * q.w + self.x * q.x + self.y * q.y + self.z * q.z
+    }
+
+    // true if both quaternions represent the same rotation (q and -q do)
+    pub fn nearly_equal(&self, q: &Quaternion) -> bool {
+        nearly_equal(self.dot(q).abs(), self.norm() * q.norm())
+    }
+
+    // rotation of v by the unit quaternion self: q v q*
+    pub fn rotate(&self, v: &Vector) -> Vector {
+        let u = Vector::new(self.x, self.y, self.z);
+        let t = 2. * (&u ^ v);
+        v + self.w * &t + (&u ^ &t)
+    }
+
+    // spherical linear interpolation from self (t = 0) to q (t = 1) along
+    // the shortest path
+    pub fn slerp(&self, q: &Quaternion, t: f64) -> Quaternion {
+        let (a, mut b) = (self.unit(), q.unit());
+        let mut cos = a.dot(&b);
+        if cos < 0. {
+            b = -1. * b;
+            cos = -cos;
+        }
+
+        if cos > 1. - 1e-9 {
+            // nearly the same rotation: linear interpolation is accurate
+            return ((1. - t) * a + t * b).unit();
+        }
+
+        let theta = cos.acos();
+        let sin = theta.sin();
+        (((1. - t) * theta).sin() / sin * a + (t * theta).sin() / sin * b).unit()
+    }
+}
+
+impl_op_ex!(+|lhs: &Quaternion, rhs: &Quaternion| -> Quaternion {
+    Quaternion::new(lhs.w + rhs.w, lhs.x + rhs.x, lhs.y + rhs.y, lhs.z + rhs.z)
+});
+
+impl_op_ex!(*|lhs: f64, rhs: &Quaternion| -> Quaternion {
+    Quaternion::new(lhs * rhs.w, lhs * rhs.x, lhs * rhs.y, lhs * rhs.z)
+});
+
+// Hamilton product: lhs * rhs rotates by rhs first, then by lhs
+impl_op_ex!(*|lhs: &Quaternion, rhs: &Quaternion| -> Quaternion {
+    Quaternion::new(
+        lhs.w * rhs.w - lhs.x * rhs.x - lhs.y * rhs.y - lhs.z * rhs.z,
+        lhs.w * rhs.x + lhs.x * rhs.w + lhs.y * rhs.z - lhs.z * rhs.y,
+        lhs.w * rhs.y - lhs.x * rhs.z + lhs.y * rhs.w + lhs.z * rhs.x,
+        lhs.w * rhs.z + lhs.x * rhs.y - lhs.y * rhs.x + lhs.z * rhs.w,
+    )
+});
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{I, J, K};
+
+    #[test]
+    fn rotate_1() {
+        let q = Quaternion::from_axis_angle(&K, 90.);
+        assert!(q.rotate(&I).nearly_equal(&J));
+
+        let q = Quaternion::from_axis_angle(&Vector::new(1., 1., 1.), 120.);
+        assert!(q.rotate(&I).nearly_equal(&J));
+        assert!(q.rotate(&J).nearly_equal(&K));
+    }
+
+    #[test]
+    fn compose_1() {
+        let qx = Quaternion::from_axis_angle(&I, 90.);
+        let qy = Quaternion::from_axis_angle(&J, 90.);
+        let v = Vector::new(1., 2., 3.);
+
+        assert!((qy * qx)
+            .rotate(&v)
+            .nearly_equal(&qy.rotate(&qx.rotate(&v))));
+        assert!((qx * qx.conjugate()).nearly_equal(&ID_QUATERNION));
+    }
+
+    #[test]
+    fn matrix_1() {
+        let q = Quaternion::from_axis_angle(&Vector::new(-1., 2., 0.5), 137.);
+        let m = q.to_matrix();
+        let v = Vector::new(0.3, -2., 1.);
+
+        assert!((&m * &v).nearly_equal(&q.rotate(&v)));
+        assert!(Quaternion::from_matrix(&m).nearly_equal(&q));
+        for deg in [0., 90., 180., 270.] {
+            for axis in [I, J, K] {
+                let q = Quaternion::from_axis_angle(&axis, deg);
+                assert!(Quaternion::from_matrix(&q.to_matrix()).nearly_equal(&q));
+            }
+        }
+    }
+
+    #[test]
+    fn slerp_1() {
+        let a = Quaternion::from_axis_angle(&K, 10.);
+        let b = Quaternion::from_axis_angle(&K, 70.);
+
+        assert!(a.slerp(&b, 0.).nearly_equal(&a));
+        assert!(a.slerp(&b, 1.).nearly_equal(&b));
+        assert!(a
+            .slerp(&b, 0.5)
+            .nearly_equal(&Quaternion::from_axis_angle(&K, 40.)));
+
+        // shortest path: from 10° to 350° goes through 0°
+        let c = Quaternion::from_axis_angle(&K, 350.);
+        assert!(a
+            .slerp(&c, 0.5)
+            .nearly_equal(&Quaternion::from_axis_angle(&K, 0.)));
+        assert!(a.slerp(&a, 0.3).nearly_equal(&a));
+    }
+}
@@ -0,0 +1,187 @@
+// Real roots of polynomials, sorted in increasing order. Multiple roots are
+// repeated as many times as their multiplicity, so that entry/exit pairs stay
+// paired for tangent rays: a quartic always has 0, 2 or 4 roots. A cubic may
+// still give roots closer than rounding only once.
+
+use std::f64::consts::PI;
+
+// a x² + b x + c, with the citardauq form for the root of smaller magnitude:
+// no cancellation between -b and sqrt(delta) when b is large
+pub fn solve_quadratic(a: f64, b: f64, c: f64) -> Vec<f64> {
+    if a == 0. {
+        return if b == 0. { vec![] } else { vec![-c / b] };
+    }
+
+    let delta = b * b - 4. * a * c;
+    if delta < 0. {
+        return vec![];
+    }
+
+    let q = -0.5 * (b + b.signum() * delta.sqrt());
+    if q == 0. {
+        // b = c = 0
+        return vec![0., 0.];
+    }
+    let (k1, k2) = (q / a, c / q);
+    vec![k1.min(k2), k1.max(k2)]
+}
+
+// a x³ + b x² + c x + d
+pub fn solve_cubic(a: f64, b: f64, c: f64, d: f64) -> Vec<f64> {
+    if a == 0. {
+        return solve_quadratic(b, c, d);
+    }
+    let (b, c, d) = (b / a, c / a, d / a);
+
+    // depressed t³ + p t + q with x = t - b/3
+    let shift = b / 3.;
+    let p = c - b * shift;
+    let q = 2. * shift * shift * shift - shift * c + d;
+    let disc = q * q / 4. + p * p * p / 27.;
+
+    let mut roots = if disc > 0. {
+        let s = disc.sqrt();
+        vec![(-q / 2. + s).cbrt() + (-q / 2. - s).cbrt()]
+    } else if p == 0. {
+        // disc = 0 too: triple root
+        vec![0., 0., 0.]
+    } else {
+        // three real roots, trigonometric form
+        let r = (-p / 3.).sqrt();
+        let phi = (-q / (2. * r * r * r)).clamp(-1., 1.).acos();
+        (0..3)
+            .map(|k| 2. * r * ((phi + 2. * PI * k as f64) / 3.).cos())
+            .collect()
+    };
+
+    for x in roots.iter_mut() {
+        *x -= shift;
+        polish(x, &[1., b, c, d]);
+    }
+    roots.sort_by(f64::total_cmp);
+    roots
+}
+
+// a x⁴ + b x³ + c x² + d x + e (Ferrari)
+pub fn solve_quartic(a: f64, b: f64, c: f64, d: f64, e: f64) -> Vec<f64> {
+    if a == 0. {
+        return solve_cubic(b, c, d, e);
+    }
+    let (b, c, d, e) = (b / a, c / a, d / a, e / a);
+
+    // depressed y⁴ + p y² + q y + r with x = y - b/4
+    let shift = b / 4.;
+    let b2 = b * b;
+    let p = c - 3. * b2 / 8.;
+    let q = d - b * c / 2. + b2 * b / 8.;
+    let r = e - b * d / 4. + b2 * c / 16. - 3. * b2 * b2 / 256.;
+
+    // q is compared to the size of the other terms (x² for p, x⁴ for r)
+    let scale = p.abs().max(r.abs().sqrt());
+    let biquadratic = q.abs() <= 1e-12 * scale * scale.sqrt();
+
+    let mut roots = if !biquadratic {
+        // resolvent cubic, its largest root m > 0 makes both sides perfect
+        // squares
+        let m = solve_cubic(1., p, p * p / 4. - r, -q * q / 8.)
+            .into_iter()
+            .fold(f64::NEG_INFINITY, f64::max);
+        let s = (2. * m).sqrt();
+        let mut roots = monic_quadratic(-s, p / 2. + m + q / (2. * s));
+        roots.extend(monic_quadratic(s, p / 2. + m - q / (2. * s)));
+        roots
+    } else {
+        solve_quadratic(1., p, r)
+            .into_iter()
+            .filter(|z| *z >= 0.)
+            .flat_map(|z| [-z.sqrt(), z.sqrt()])
+            .collect()
+    };
+
+    for x in roots.iter_mut() {
+        *x -= shift;
+        polish(x, &[1., b, c, d, e]);
+    }
+    roots.sort_by(f64::total_cmp);
+    roots
+}
+
+// x² + b x + c where rounding of the coefficients may turn a double root
+// into a slightly negative delta
+fn monic_quadratic(b: f64, c: f64) -> Vec<f64> {
+    let delta = b * b - 4. * c;
+    if delta < 0. && delta > -1e-9 * (b * b + 4. * c.abs()) {
+        vec![-b / 2., -b / 2.]
+    } else {
+        solve_quadratic(1., b, c)
+    }
+}
+
+// two Newton steps on the polynomial of coefficients k, highest degree first
+fn polish(x: &mut f64, k: &[f64]) {
+    for _ in 0..2 {
+        let (f, df) = k
+            .iter()
+            .fold((0., 0.), |(f, df), k| (f * *x + k, df * *x + f));
+        if df != 0. {
+            *x -= f / df;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nearly_equal;
+
+    fn check(roots: Vec<f64>, expected: &[f64]) {
+        assert_eq!(roots.len(), expected.len(), "{roots:?}");
+        for (k, e) in roots.iter().zip(expected) {
+            assert!(nearly_equal(*k, *e), "{roots:?}");
+        }
+    }
+
+    #[test]
+    fn quadratic() {
+        check(solve_quadratic(1., -3., 2.), &[1., 2.]);
+        check(solve_quadratic(2., 0., -8.), &[-2., 2.]);
+        check(solve_quadratic(1., 2., 1.), &[-1., -1.]);
+        check(solve_quadratic(1., 0., 1.), &[]);
+        check(solve_quadratic(0., 2., -1.), &[0.5]);
+        check(solve_quadratic(1., 0., 0.), &[0., 0.]);
+
+        // (x - 1e-8)(x - 1e8): the textbook formula loses the small root
+        let roots = solve_quadratic(1., -(1e8 + 1e-8), 1.);
+        assert!((roots[0] - 1e-8).abs() < 1e-20);
+        assert!(nearly_equal(roots[1], 1e8));
+    }
+
+    #[test]
+    fn cubic() {
+        // (x - 1)(x - 2)(x - 3)
+        check(solve_cubic(1., -6., 11., -6.), &[1., 2., 3.]);
+        // 2(x + 1)(x² + 1)
+        check(solve_cubic(2., 2., 2., 2.), &[-1.]);
+        check(solve_cubic(1., 0., 0., -8.), &[2.]);
+        check(solve_cubic(0., 1., -3., 2.), &[1., 2.]);
+        // (x - 2)³ and (x - 1)²(x - 4)
+        check(solve_cubic(1., -6., 12., -8.), &[2., 2., 2.]);
+        check(solve_cubic(1., -6., 9., -4.), &[1., 1., 4.]);
+    }
+
+    #[test]
+    fn quartic() {
+        // (x - 1)(x - 2)(x + 3)(x - 4) = x⁴ - 4x³ - 7x² + 34x - 24
+        check(solve_quartic(1., -4., -7., 34., -24.), &[-3., 1., 2., 4.]);
+
+        // x⁴ + 1 has no real root, 2(x² - 4)(x² + 1) two
+        check(solve_quartic(1., 0., 0., 0., 1.), &[]);
+        check(solve_quartic(2., 0., -6., 0., -8.), &[-2., 2.]);
+
+        // (x - 1)²(x - 2)(x - 5) = x⁴ - 9x³ + 25x² - 27x + 10
+        let roots = solve_quartic(1., -9., 25., -27., 10.);
+        assert_eq!(roots.len(), 4);
+        assert!((roots[0] - 1.).abs() < 1e-6 && (roots[1] - 1.).abs() < 1e-6);
+        assert!(nearly_equal(roots[2], 2.) && nearly_equal(roots[3], 5.));
+    }
+}
@@ -0,0 +1,364 @@
+// Wavefront OBJ import.
+//
+// Supported statements:
+//   v x y z [w]          vertex position (w ignored)
+//   vt u [v [w]]         texture coordinates
+//   vn x y z             vertex normal
+//   f v1/vt1/vn1 ...     polygon, fan-triangulated; vt and vn are optional
+//                        (v, v/vt, v//vn, v/vt/vn), negative indices count
+//                        back from the last element read
+//   g name, o name       start a new group
+//   usemtl name          material of the following faces
+//   mtllib file          material library, see parse_mtl
+// Other statements (s, l, p, ...) are ignored.
+//
+// Every (group, material) pair gives a mesh with its own vertex buffer.
+// Degenerate triangles are skipped.
+// Normals and texture coordinates are kept only if every face of the mesh
+// provides them.
+
+use std::collections::HashMap;
+
+use crate::{Mesh, ParseError, Point, Triangle, Vector};
+
+mod mtl;
+
+pub use mtl::parse_mtl;
+
+pub struct ObjGroup {
+    pub name: String,
+    pub material: Option<String>,
+    pub mesh: Mesh,
+}
+
+pub struct Obj {
+    pub groups: Vec<ObjGroup>,
+    pub mtllibs: Vec<String>,
+}
+
+// face corner: position, texcoord and normal indices
+type Corner = (usize, Option<usize>, Option<usize>);
+
+// faces of one group before meshing
+struct Part {
+    name: String,
+    material: Option<String>,
+    faces: Vec<Vec<Corner>>,
+}
+
+impl Obj {
+    pub fn parse(src: &str) -> Result<Obj, ParseError> {
+        let mut positions: Vec<Point> = vec![];
+        let mut texcoords: Vec<(f64, f64)> = vec![];
+        let mut normals: Vec<Vector> = vec![];
+        let mut mtllibs = vec![];
+        let mut parts: Vec<Part> = vec![];
+        let mut current = Part {
+            name: "default".to_owned(),
+            material: None,
+            faces: vec![],
+        };
+
+        for (n, line) in src.lines().enumerate() {
+            let line_no = n + 1;
+            let fields = fields(line);
+            let Some(&(_, keyword)) = fields.first() else {
+                continue;
+            };
+            let args = &fields[1..];
+
+            match keyword {
+                "v" => {
+                    let c = numbers(line_no, line, args, 3, 4)?;
+                    positions.push(Point::new(c[0], c[1], c[2]));
+                }
+                "vt" => {
+                    let c = numbers(line_no, line, args, 1, 3)?;
+                    texcoords.push((c[0], c.get(1).copied().unwrap_or(0.)));
+                }
+                "vn" => {
+                    let c = numbers(line_no, line, args, 3, 3)?;
+                    normals.push(Vector::new(c[0], c[1], c[2]));
+                }
+                "f" => {
+                    if args.len() < 3 {
+                        return Err(ParseError::new(
+                            line_no,
+                            end_col(line),
+                            "face needs at least 3 vertices",
+                        ));
+                    }
+                    let counts = (positions.len(), texcoords.len(), normals.len());
+                    let face = args
+                        .iter()
+                        .map(|(col, f)| face_vertex(line_no, *col, f, counts))
+                        .collect::<Result<Vec<_>, _>>()?;
+                    current.faces.push(face);
+                }
+                "g" | "o" | "usemtl" => {
+                    let name = args
+                        .iter()
+                        .map(|(_, s)| *s)
+                        .collect::<Vec<&str>>()
+                        .join(" ");
+                    if name.is_empty() {
+                        return Err(ParseError::new(line_no, end_col(line), "name expected"));
+                    }
+
+                    let mut next = Part {
+                        name: current.name.clone(),
+                        material: current.material.clone(),
+                        faces: vec![],
+                    };
+                    if keyword == "usemtl" {
+                        next.material = Some(name);
+                    } else {
+                        next.name = name;
+                    }
+                    let done = std::mem::replace(&mut current, next);
+                    if !done.faces.is_empty() {
+                        parts.push(done);
+                    }
+                }
+                "mtllib" => {
+                    mtllibs.extend(args.iter().map(|(_, s)| s.to_string()));
+                }
+                _ => (),
+            }
+        }
+        if !current.faces.is_empty() {
+            parts.push(current);
+        }
+
+        let groups = parts
+            .into_iter()
+            .map(|part| ObjGroup {
+                mesh: build_mesh(&part, &positions, &texcoords, &normals),
+                name: part.name,
+                material: part.material,
+            })
+            .collect();
+
+        Ok(Obj { groups, mtllibs })
+    }
+}
+
+fn build_mesh(
+    part: &Part,
+    positions: &[Point],
+    texcoords: &[(f64, f64)],
+    normals: &[Vector],
+) -> Mesh {
+    let corners = || part.faces.iter().flatten();
+    let has_uvs = corners().all(|c| c.1.is_some());
+    let has_normals = corners().all(|c| c.2.is_some());
+
+    // one mesh vertex per distinct corner
+    let mut index: HashMap<Corner, usize> = HashMap::new();
+    let mut vertices = vec![];
+    let mut uvs = vec![];
+    let mut vertex_normals = vec![];
+    let mut triangles = vec![];
+
+    for face in &part.faces {
+        let ids: Vec<usize> = face
+            .iter()
+            .map(|&(v, vt, vn)| {
+                let key = (v, vt.filter(|_| has_uvs), vn.filter(|_| has_normals));
+                *index.entry(key).or_insert_with(|| {
+                    vertices.push(positions[v].clone());
+                    if let Some(vt) = key.1 {
+                        uvs.push(texcoords[vt]);
+                    }
+                    if let Some(vn) = key.2 {
+                        vertex_normals.push(normals[vn].clone());
+                    }
+                    vertices.len() - 1
+                })
+            })
+            .collect();
+
+        // degenerate triangles (aligned or repeated corners) are dropped
+        for k in 1..ids.len() - 1 {
+            let [a, b, c] = [ids[0], ids[k], ids[k + 1]];
+            if !Triangle::is_degenerate(&vertices[a], &vertices[b], &vertices[c]) {
+                triangles.push([a, b, c]);
+            }
+        }
+    }
+
+    let mut mesh = Mesh::build(vertices, triangles);
+    if has_uvs {
+        mesh = mesh.with_uvs(uvs);
+    }
+    if has_normals {
+        mesh = mesh.with_normals(vertex_normals);
+    }
+    mesh
+}
+
+// one face corner: v, v/vt, v//vn or v/vt/vn
+fn face_vertex(
+    line: usize,
+    col: usize,
+    field: &str,
+    counts: (usize, usize, usize),
+) -> Result<Corner, ParseError> {
+    let mut parts = field.split('/');
+    let v = parts.next().unwrap_or("");
+    let vt = parts.next().filter(|s| !s.is_empty());
+    let vn = parts.next().filter(|s| !s.is_empty());
+    if parts.next().is_some() {
+        return Err(ParseError::new(line, col, "invalid face vertex"));
+    }
+
+    let resolve = |s: &str, count: usize| -> Result<usize, ParseError> {
+        let i: i64 = s
+            .parse()
+            .map_err(|_| ParseError::new(line, col, "index expected"))?;
+        let i = if i > 0 { i - 1 } else { count as i64 + i };
+        if i < 0 || i >= count as i64 {
+            return Err(ParseError::new(line, col, "index out of range"));
+        }
+        Ok(i as usize)
+    };
+
+    Ok((
+        resolve(v, counts.0)?,
+        vt.map(|s| resolve(s, counts.1)).transpose()?,
+        vn.map(|s| resolve(s, counts.2)).transpose()?,
+    ))
+}
+
+// whitespace separated fields with their 1-based column, comments removed
+fn fields(line: &str) -> Vec<(usize, &str)> {
+    let line = line.split('#').next().unwrap_or("");
+    let mut fields = vec![];
+    let mut start = None;
+
+    for (i, c) in line.char_indices() {
+        match (c.is_whitespace(), start) {
+            (false, None) => start = Some(i),
+            (true, Some(s)) => {
+                fields.push((line[..s].chars().count() + 1, &line[s..i]));
+                start = None;
+            }
+            _ => (),
+        }
+    }
+    if let Some(s) = start {
+        fields.push((line[..s].chars().count() + 1, &line[s..]));
+    }
+    fields
+}
+
+fn end_col(line: &str) -> usize {
+    line.trim_end().chars().count() + 1
+}
+
+// between min and max numbers
+fn numbers(
+    line_no: usize,
+    line: &str,
+    args: &[(usize, &str)],
+    min: usize,
+    max: usize,
+) -> Result<Vec<f64>, ParseError> {
+    if args.len() < min {
+        return Err(ParseError::new(line_no, end_col(line), "number expected"));
+    }
+    if args.len() > max {
+        return Err(ParseError::new(line_no, args[max].0, "too many values"));
+    }
+
+    args.iter()
+        .map(|(col, s)| {
+            s.parse::<f64>()
+                .map_err(|_| ParseError::new(line_no, *col, "number expected"))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{nearly_equal, Camera, Ray, Shapes};
+
+    const QUADS: &str = "
+# two unit squares
+mtllib scene.mtl
+v -1 -1 0
+v  1 -1 0
+v  1  1 0
+v -1  1 0
+vt 0 0
+vt 1 0
+vt 1 1
+vt 0 1
+vn 0 0 -1
+
+g front
+usemtl red
+f 1/1/1 2/2/1 3/3/1 4/4/1
+g back
+usemtl blue
+f -4 -1 -2
+f -4 -2 -3
+";
+
+    #[test]
+    fn parse_1() {
+        let obj = Obj::parse(QUADS).unwrap();
+        assert_eq!(obj.mtllibs, vec!["scene.mtl"]);
+        assert_eq!(obj.groups.len(), 2);
+
+        let front = &obj.groups[0];
+        assert_eq!(front.name, "front");
+        assert_eq!(front.material.as_deref(), Some("red"));
+        assert_eq!(front.mesh.vertices().len(), 4);
+        assert_eq!(front.mesh.indices(), &[[0, 1, 2], [0, 2, 3]]);
+
+        let back = &obj.groups[1];
+        assert_eq!(back.name, "back");
+        assert_eq!(back.material.as_deref(), Some("blue"));
+        assert_eq!(back.mesh.indices(), &[[0, 1, 2], [0, 2, 3]]);
+        assert!(back.mesh.vertices()[1].nearly_equal(&Point::new(-1., 1., 0.)));
+    }
+
+    #[test]
+    fn degenerate() {
+        // the fan of this quad starts with three aligned corners
+        let obj = Obj::parse("v 0 0 0\nv 1 0 0\nv 2 0 0\nv 0 1 0\nf 1 2 3 4\n").unwrap();
+        assert_eq!(obj.groups[0].mesh.indices(), &[[0, 2, 3]]);
+    }
+
+    #[test]
+    fn uv() {
+        let mut obj = Obj::parse(QUADS).unwrap();
+        let mut mesh = obj.groups.remove(0).mesh;
+        mesh.compute_camcs_to_shapecs(&Camera::new());
+
+        let ray = Ray::new(Point::new(0.5, 0., -5.), Vector::new(0., 0., 1.));
+        let hit = mesh.hit(&ray).unwrap();
+        assert!(nearly_equal(hit.t, 5.));
+        let (u, v) = hit.uv.unwrap();
+        assert!(nearly_equal(u, 0.75));
+        assert!(nearly_equal(v, 0.5));
+    }
+
+    #[test]
+    fn errors() {
+        let check = |src: &str, line: usize, col: usize, msg: &str| {
+            let err = Obj::parse(src).err().unwrap();
+            assert_eq!((err.line, err.col, err.msg.as_str()), (line, col, msg));
+        };
+
+        check("v 1 2\n", 1, 6, "number expected");
+        check("v 0 0 0\nv 1 x 0\n", 2, 5, "number expected");
+        check("v 0 0 0\nf 1 1\n", 2, 6, "face needs at least 3 vertices");
+        check("v 0 0 0\nf 1 1 2\n", 2, 7, "index out of range");
+        check("v 0 0 0\nf 1 1 1/1\n", 2, 7, "index out of range");
+        check("v 0 0 0\nf 1 0 1\n", 2, 5, "index out of range");
+        check("usemtl\n", 1, 7, "name expected");
+    }
+}
@@ -0,0 +1,157 @@
+// Wavefront MTL import, onto crate materials:
+//   newmtl name    start a material
+//   Kd r g b       color
+//   Ks r g b       specular (mean of the components)
+//   Ns e           shininess
+//   Ni ior         index of refraction, positive
+//   d a            opacity in [0, 1], transparency is 1 - a
+//   Tr t           transparency in [0, 1]
+// Other statements (Ka, illum, map_Kd, ...) are ignored.
+
+use std::collections::HashMap;
+
+use super::{end_col, fields, numbers};
+use crate::{Color, Material, ParseError};
+
+pub fn parse_mtl(src: &str) -> Result<HashMap<String, Material>, ParseError> {
+    let mut materials = HashMap::new();
+    let mut current: Option<(String, Material)> = None;
+
+    for (n, line) in src.lines().enumerate() {
+        let line_no = n + 1;
+        let fields = fields(line);
+        let Some(&(col, keyword)) = fields.first() else {
+            continue;
+        };
+        let args = &fields[1..];
+
+        if keyword == "newmtl" {
+            let Some(&(_, name)) = args.first() else {
+                return Err(ParseError::new(line_no, end_col(line), "name expected"));
+            };
+            if let Some((name, m)) = current.take() {
+                materials.insert(name, m);
+            }
+            current = Some((name.to_owned(), Material::default()));
+            continue;
+        }
+
+        let known = ["Kd", "Ks", "Ns", "Ni", "d", "Tr"];
+        if !known.contains(&keyword) {
+            continue;
+        }
+        let Some((_, m)) = current.as_mut() else {
+            return Err(ParseError::new(line_no, col, "newmtl expected"));
+        };
+
+        match keyword {
+            "Kd" => {
+                let c = numbers(line_no, line, args, 3, 3)?;
+                m.color = Color::new(c[0], c[1], c[2]);
+            }
+            "Ks" => {
+                let c = numbers(line_no, line, args, 3, 3)?;
+                m.specular = (c[0] + c[1] + c[2]) / 3.;
+            }
+            "Ns" => m.shininess = numbers(line_no, line, args, 1, 1)?[0],
+            // ior is a divisor, opacity and transparency are fractions
+            "Ni" => m.ior = number_in(line_no, line, args, |k| k > 0., "positive number")?,
+            "d" => {
+                m.transparency = 1. - number_in(line_no, line, args, fraction, "number in [0, 1]")?
+            }
+            "Tr" => m.transparency = number_in(line_no, line, args, fraction, "number in [0, 1]")?,
+            _ => unreachable!(),
+        }
+    }
+    if let Some((name, m)) = current {
+        materials.insert(name, m);
+    }
+
+    Ok(materials)
+}
+
+fn fraction(k: f64) -> bool {
+    (0. ..=1.).contains(&k)
+}
+
+// single number checked by valid, error located at the number
+fn number_in(
+    line_no: usize,
+    line: &str,
+    args: &[(usize, &str)],
+    valid: fn(f64) -> bool,
+    expected: &str,
+) -> Result<f64, ParseError> {
+    let k = numbers(line_no, line, args, 1, 1)?[0];
+    if valid(k) {
+        Ok(k)
+    } else {
+        Err(ParseError::new(
+            line_no,
+            args[0].0,
+            &format!("{expected} expected"),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nearly_equal;
+
+    #[test]
+    fn parse_1() {
+        let src = "
+# glass and red
+newmtl glass
+Kd 0.9 0.9 1.0
+Ks 1 1 1
+Ns 200
+Ni 1.5
+d 0.1
+illum 4
+
+newmtl red
+Ka 0.1 0 0
+Kd 1 0 0
+";
+        let materials = parse_mtl(src).unwrap();
+        assert_eq!(materials.len(), 2);
+
+        let glass = &materials["glass"];
+        assert!(glass.color.nearly_equal(&Color::new(0.9, 0.9, 1.)));
+        assert!(nearly_equal(glass.specular, 1.));
+        assert!(nearly_equal(glass.shininess, 200.));
+        assert!(nearly_equal(glass.ior, 1.5));
+        assert!(nearly_equal(glass.transparency, 0.9));
+
+        let red = &materials["red"];
+        assert!(red.color.nearly_equal(&Color::new(1., 0., 0.)));
+        assert!(nearly_equal(red.transparency, 0.));
+    }
+
+    #[test]
+    fn errors() {
+        let err = parse_mtl("Kd 1 0 0\n").err().unwrap();
+        assert_eq!(
+            (err.line, err.col, err.msg.as_str()),
+            (1, 1, "newmtl expected")
+        );
+
+        let err = parse_mtl("newmtl a\n  Ns high\n").err().unwrap();
+        assert_eq!(
+            (err.line, err.col, err.msg.as_str()),
+            (2, 6, "number expected")
+        );
+
+        let err = parse_mtl("newmtl a\nNi 0\n").err().unwrap();
+        assert_eq!(
+            (err.line, err.col, err.msg.as_str()),
+            (2, 4, "positive number expected")
+        );
+        for (src, col) in [("d 1.5", 3), ("d -0.1", 3), ("Tr 2", 4)] {
+            let err = parse_mtl(&format!("newmtl a\n{src}\n")).err().unwrap();
+            assert_eq!((err.line, err.col), (2, col));
+        }
+    }
+}
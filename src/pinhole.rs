@@ -1,9 +1,11 @@
 mod camera;
 mod focale;
 mod image;
+mod pattern;
 mod sampler;
 
 pub use camera::Camera;
 pub use focale::Focale;
-pub use image::ImageSize;
-pub use sampler::Sampler;
+pub use image::{ImageSize, MAX_PIXELS};
+pub use pattern::Pattern;
+pub use sampler::{Sample, Sampler, Window};
@@ -1,7 +1,7 @@
 use std::fmt::Display;
 
-use super::{Focale, ImageSize, Sampler};
-use crate::{Cs, Matrix, Point, O, POINT_K};
+use super::{Focale, ImageSize, Pattern, Sampler, Window};
+use crate::{Cs, Filter, Matrix, Point, MAX_RADIUS, O, POINT_K};
 
 pub struct Camera {
     location: Point,
@@ -9,6 +9,11 @@ pub struct Camera {
     moved: bool,
     image_size: ImageSize,
     focale: Focale,
+    samples: u32,
+    pattern: Pattern,
+    filter: Filter,
+    filter_radius: f64,
+    crop: Option<Window>,
     cs: Cs,
 }
 
@@ -26,6 +31,11 @@ impl Default for Camera {
             moved: false,
             image_size: ImageSize::default(),
             focale: Focale::default(),
+            samples: 1,
+            pattern: Pattern::default(),
+            filter: Filter::default(),
+            filter_radius: Filter::default().default_radius(),
+            crop: None,
             cs: Cs::default(),
         }
     }
@@ -36,14 +46,35 @@ impl Camera {
         Camera::default()
     }
 
-    pub fn iter(&mut self) -> Sampler {
+    pub fn iter(&mut self) -> Result<Sampler, &'static str> {
+        self.update()?;
+        Ok(self.sampler())
+    }
+
+    // apply pending move_to/look_at to the camera cs
+    pub fn update(&mut self) -> Result<&mut Self, &'static str> {
         if self.moved {
             let a = self.location.clone();
             let b = self.look_at.clone();
 
-            self.move_and_point_to(&a, &b);
+            self.move_and_point_to(&a, &b)?;
+        }
+        Ok(self)
+    }
+
+    // rays of the crop window, or of the whole image
+    pub fn sampler(&self) -> Sampler {
+        let sampler =
+            Sampler::with_samples(&self.image_size, self.focale.get_focale(), self.samples)
+                .with_pattern(self.pattern);
+        match self.crop {
+            Some(window) => sampler.with_window(window),
+            None => sampler,
         }
-        Sampler::new(&self.image_size, self.focale.get_focale())
+    }
+
+    pub fn get_image_size(&self) -> &ImageSize {
+        &self.image_size
     }
 
     pub fn get_matrix_to_lcs(&self) -> &Matrix {
@@ -54,6 +85,14 @@ impl Camera {
         self.cs.get_matrix_to_rcs()
     }
 
+    pub fn get_location(&self) -> &Point {
+        &self.location
+    }
+
+    pub fn get_look_at(&self) -> &Point {
+        &self.look_at
+    }
+
     pub fn move_to(&mut self, p: Point) -> &mut Self {
         self.location = p;
         self.moved = true;
@@ -66,10 +105,11 @@ impl Camera {
         self
     }
 
-    pub fn move_and_point_to(&mut self, a: &Point, b: &Point) -> &mut Self {
-        self.cs.complete_cs(a, &(b - a).unit());
+    // fails if a and b are the same point
+    pub fn move_and_point_to(&mut self, a: &Point, b: &Point) -> Result<&mut Self, &'static str> {
+        self.cs.complete_cs(a, &(b - a).unit())?;
         self.moved = false;
-        self
+        Ok(self)
     }
 
     pub fn set_focale(&mut self, focale: Focale) -> &mut Self {
@@ -81,6 +121,43 @@ impl Camera {
         self.image_size = ImageSize::new(width, height);
         self
     }
+
+    // rays per pixel
+    pub fn set_samples(&mut self, samples: u32) -> &mut Self {
+        assert!(samples > 0);
+        self.samples = samples;
+        self
+    }
+
+    pub fn get_samples(&self) -> u32 {
+        self.samples
+    }
+
+    pub fn set_pattern(&mut self, pattern: Pattern) -> &mut Self {
+        self.pattern = pattern;
+        self
+    }
+
+    // reconstruction filter of the rendered image, radius in pixels
+    pub fn set_filter(&mut self, filter: Filter, radius: f64) -> &mut Self {
+        assert!(radius > 0. && radius <= MAX_RADIUS);
+        self.filter = filter;
+        self.filter_radius = radius;
+        self
+    }
+
+    pub fn get_filter(&self) -> (Filter, f64) {
+        (self.filter, self.filter_radius)
+    }
+
+    // render only this part of the image, None for the whole image
+    pub fn set_crop(&mut self, crop: Option<Window>) -> &mut Self {
+        if let Some(w) = crop {
+            assert!(w.x1 <= self.image_size.width && w.y1 <= self.image_size.height);
+        }
+        self.crop = crop;
+        self
+    }
 }
 
 #[cfg(test)]
@@ -101,6 +178,7 @@ mod tests {
                     &Point::new(1., 2., 3.),
                     &SphCoord::build(12., theta, phy).into_point(),
                 )
+                .unwrap()
                 .set_focale(Focale::AngleDeg(90.))
                 .set_image_size(640, 480);
                 let lcs = cam.cs.get_matrix_to_lcs();
@@ -119,8 +197,12 @@ mod tests {
         cam.move_to(Point::new(1., 12., 3.))
             .look_at(Point::new(-12., 34., -4.3))
             .set_image_size(1, 1);
-        for (_, _, ray) in cam.iter() {
-            println!("{}", ray);
-        }
+
+        // the single pixel ray goes through the film center, at look_at
+        let samples: Vec<_> = cam.iter().unwrap().collect();
+        assert_eq!(samples.len(), 1);
+        let v = cam.get_matrix_to_rcs() * &samples[0].ray.v;
+        let expected = (Point::new(-12., 34., -4.3) - Point::new(1., 12., 3.)).unit();
+        assert!(v.nearly_equal(&expected));
     }
 }
@@ -9,6 +9,9 @@ pub struct ImageSize {
 pub const DEFAULT_WIDTH: u32 = 320;
 pub const DEFAULT_HEIGHT: u32 = 240;
 
+// largest image accepted from a scene or the command line, 8192x8192
+pub const MAX_PIXELS: u64 = 1 << 26;
+
 impl Default for ImageSize {
     fn default() -> ImageSize {
         ImageSize {
@@ -26,4 +29,8 @@ impl ImageSize {
     pub fn new(width: u32, height: u32) -> ImageSize {
         ImageSize { width, height }
     }
+
+    pub fn is_too_large(&self) -> bool {
+        self.width as u64 * self.height as u64 > MAX_PIXELS
+    }
 }
@@ -0,0 +1,214 @@
+use std::fmt::Display;
+use std::str::FromStr;
+
+// Sub-pixel sample layouts. Positions are in [0, 1)² within the pixel and
+// depend only on pixel coordinates and sample index: images are
+// reproducible. Low-discrepancy sequences are shifted per pixel so that
+// neighbouring pixels do not share the same pattern.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Pattern {
+    #[default]
+    Regular, // centers of a sub-pixel grid
+    Jittered, // one random point in each grid cell
+    Random,
+    Halton, // bases 2 and 3
+    Sobol,  // first two dimensions
+    R2,     // additive recurrence on the plastic number
+}
+
+const PATTERNS: [(&str, Pattern); 6] = [
+    ("regular", Pattern::Regular),
+    ("jittered", Pattern::Jittered),
+    ("random", Pattern::Random),
+    ("halton", Pattern::Halton),
+    ("sobol", Pattern::Sobol),
+    ("r2", Pattern::R2),
+];
+
+impl Display for Pattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (name, _) = PATTERNS.iter().find(|(_, p)| p == self).unwrap();
+        write!(f, "{name}")
+    }
+}
+
+impl FromStr for Pattern {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        PATTERNS
+            .iter()
+            .find(|(name, _)| *name == s)
+            .map(|(_, p)| *p)
+            .ok_or_else(|| format!("unknown sample pattern '{s}'"))
+    }
+}
+
+impl Pattern {
+    // true if the first samples of any count cover the pixel evenly: grid
+    // patterns fill the sub-pixel rows in order, only their full count does
+    pub fn is_progressive(&self) -> bool {
+        !matches!(self, Pattern::Regular | Pattern::Jittered)
+    }
+
+    // position of sample s of n in pixel (x, y)
+    pub fn position(&self, x: u32, y: u32, s: u32, n: u32) -> (f64, f64) {
+        let grid = (n as f64).sqrt().ceil() as u32;
+        let rows = n.div_ceil(grid);
+        let cell = ((s % grid) as f64, (s / grid) as f64);
+
+        match self {
+            Pattern::Regular => ((cell.0 + 0.5) / grid as f64, (cell.1 + 0.5) / rows as f64),
+            Pattern::Jittered => (
+                (cell.0 + unit(hash3(x, y, 2 * s))) / grid as f64,
+                (cell.1 + unit(hash3(x, y, 2 * s + 1))) / rows as f64,
+            ),
+            Pattern::Random => (unit(hash3(x, y, 2 * s)), unit(hash3(x, y, 2 * s + 1))),
+            Pattern::Halton => (
+                shift(radical_inverse(2, s), hash3(x, y, 0)),
+                shift(radical_inverse(3, s), hash3(x, y, 1)),
+            ),
+            Pattern::Sobol => (
+                unit(s.reverse_bits() ^ hash3(x, y, 0)),
+                unit(sobol_2(s) ^ hash3(x, y, 1)),
+            ),
+            Pattern::R2 => {
+                // 1/g and 1/g² where g³ = g + 1
+                const A1: f64 = 0.754_877_666_246_692_8;
+                const A2: f64 = 0.569_840_290_998_053_3;
+                (
+                    shift(0.5 + A1 * s as f64, hash3(x, y, 0)),
+                    shift(0.5 + A2 * s as f64, hash3(x, y, 1)),
+                )
+            }
+        }
+    }
+}
+
+// integer hash with good avalanche (lowbias32)
+fn hash(mut k: u32) -> u32 {
+    k ^= k >> 16;
+    k = k.wrapping_mul(0x7feb_352d);
+    k ^= k >> 15;
+    k = k.wrapping_mul(0x846c_a68b);
+    k ^ (k >> 16)
+}
+
+fn hash3(a: u32, b: u32, c: u32) -> u32 {
+    hash(a ^ hash(b ^ hash(c)))
+}
+
+fn unit(k: u32) -> f64 {
+    k as f64 / 4_294_967_296.
+}
+
+// toroidal shift of k in [0, 1) by a random amount
+fn shift(k: f64, h: u32) -> f64 {
+    (k + unit(h)).fract()
+}
+
+fn radical_inverse(base: u32, mut s: u32) -> f64 {
+    let inv = 1. / base as f64;
+    let (mut k, mut f) = (0., inv);
+    while s > 0 {
+        k += (s % base) as f64 * f;
+        s /= base;
+        f *= inv;
+    }
+    k
+}
+
+// second Sobol dimension, direction numbers v(i) = v(i-1) ^ v(i-1) >> 1
+fn sobol_2(mut s: u32) -> u32 {
+    let (mut k, mut v) = (0, 1 << 31);
+    while s > 0 {
+        if s & 1 == 1 {
+            k ^= v;
+        }
+        s >>= 1;
+        v ^= v >> 1;
+    }
+    k
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nearly_equal;
+
+    const ALL: [Pattern; 6] = [
+        Pattern::Regular,
+        Pattern::Jittered,
+        Pattern::Random,
+        Pattern::Halton,
+        Pattern::Sobol,
+        Pattern::R2,
+    ];
+
+    #[test]
+    fn in_pixel() {
+        for p in ALL {
+            let mut mean = (0., 0.);
+            for s in 0..256 {
+                let (u, v) = p.position(3, 7, s, 256);
+                assert!((0. ..1.).contains(&u) && (0. ..1.).contains(&v), "{p}");
+                assert_eq!((u, v), p.position(3, 7, s, 256));
+                mean = (mean.0 + u / 256., mean.1 + v / 256.);
+            }
+            assert!(
+                (mean.0 - 0.5).abs() < 0.05 && (mean.1 - 0.5).abs() < 0.05,
+                "{p}"
+            );
+        }
+    }
+
+    #[test]
+    fn stratified() {
+        // one sample per cell of the 4x4 grid
+        for p in [Pattern::Regular, Pattern::Jittered, Pattern::Sobol] {
+            let mut cells = [0; 16];
+            for s in 0..16 {
+                let (u, v) = p.position(5, 2, s, 16);
+                cells[(v * 4.) as usize * 4 + (u * 4.) as usize] += 1;
+            }
+            if p == Pattern::Sobol {
+                // (0, 2)-sequence: stratified on every elementary interval,
+                // here 16x1 columns
+                let mut cols = [0; 16];
+                for s in 0..16 {
+                    cols[(p.position(5, 2, s, 16).0 * 16.) as usize] += 1;
+                }
+                assert_eq!(cols, [1; 16]);
+            }
+            assert_eq!(cells, [1; 16], "{p}");
+        }
+    }
+
+    #[test]
+    fn progressive() {
+        // the first quarter of the samples spans the pixel height
+        for p in ALL.into_iter().filter(|p| p.is_progressive()) {
+            let v: Vec<f64> = (0..16).map(|s| p.position(5, 2, s, 64).1).collect();
+            let mean = v.iter().sum::<f64>() / 16.;
+            assert!((mean - 0.5).abs() < 0.15, "{p}");
+        }
+        let v = Pattern::Regular.position(5, 2, 15, 64).1;
+        assert!(v < 0.25);
+    }
+
+    #[test]
+    fn sequences() {
+        assert!(nearly_equal(radical_inverse(2, 6), 0.375));
+        assert!(nearly_equal(radical_inverse(3, 5), 7. / 9.));
+        let sobol: Vec<f64> = (0..4).map(|s| unit(sobol_2(s))).collect();
+        assert_eq!(sobol, [0., 0.5, 0.75, 0.25]);
+    }
+
+    #[test]
+    fn names() {
+        for p in ALL {
+            assert_eq!(p.to_string().parse::<Pattern>(), Ok(p));
+        }
+        assert!("poisson".parse::<Pattern>().is_err());
+    }
+}
@@ -1,60 +1,283 @@
-use super::ImageSize;
+use super::{ImageSize, Pattern};
 use crate::{Point, Ray, Vector};
 
+// One camera ray through pixel (x, y); offset is the sample position
+// relative to the pixel center, in pixels, each coordinate in [-0.5, 0.5).
+pub struct Sample {
+    pub x: u32,
+    pub y: u32,
+    pub offset: (f64, f64),
+    pub ray: Ray,
+}
+
+impl Sample {
+    // continuous image coordinates, pixel (x, y) covers [x, x + 1) x [y, y + 1)
+    pub fn position(&self) -> (f64, f64) {
+        (
+            self.x as f64 + 0.5 + self.offset.0,
+            self.y as f64 + 0.5 + self.offset.1,
+        )
+    }
+}
+
+// Pixel rectangle [x0, x1) x [y0, y1)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Window {
+    pub x0: u32,
+    pub y0: u32,
+    pub x1: u32,
+    pub y1: u32,
+}
+
+impl Window {
+    pub fn new(x0: u32, y0: u32, x1: u32, y1: u32) -> Window {
+        assert!(x0 < x1 && y0 < y1);
+        Window { x0, y0, x1, y1 }
+    }
+
+    pub fn full(size: &ImageSize) -> Window {
+        Window::new(0, 0, size.width, size.height)
+    }
+
+    pub fn width(&self) -> u32 {
+        self.x1 - self.x0
+    }
+
+    pub fn height(&self) -> u32 {
+        self.y1 - self.y0
+    }
+
+    pub fn contains(&self, x: u32, y: u32) -> bool {
+        x >= self.x0 && x < self.x1 && y >= self.y0 && y < self.y1
+    }
+
+    // size x size tiles covering the window row by row, clipped on the right
+    // and bottom edges
+    pub fn tiles(&self, size: u32) -> Vec<Window> {
+        assert!(size > 0);
+        (self.y0..self.y1)
+            .step_by(size as usize)
+            .flat_map(|y| {
+                (self.x0..self.x1).step_by(size as usize).map(move |x| {
+                    Window::new(x, y, (x + size).min(self.x1), (y + size).min(self.y1))
+                })
+            })
+            .collect()
+    }
+}
+
+// Camera rays of every pixel of the window, pixel after pixel, row by row.
+// The film spans [-0.5, 0.5] horizontally at distance focale, pixel (x, y)
+// covers [x, x + 1) x [y, y + 1) of it in image coordinates.
 pub struct Sampler {
     focale: f64,
-    max_x: u32,
-    max_y: u32,
-    fac_x: f64,
-    fac_y: f64,
+    scale: f64,
     hlf_h: f64,
+    size: ImageSize,
+    window: Window,
+    samples: u32,
+    pattern: Pattern,
+    s0: u32,
+    s1: u32,
     x: u32,
     y: u32,
+    s: u32,
 }
 
 impl Sampler {
     pub fn new(size: &ImageSize, focale: f64) -> Sampler {
-        let h = (size.height as f64) / (size.width as f64);
-        let max_x = size.width - 1;
-        let max_y = size.height - 1;
+        Sampler::with_samples(size, focale, 1)
+    }
+
+    // samples rays per pixel, laid out on a regular sub-pixel grid unless
+    // a pattern is given with with_pattern
+    pub fn with_samples(size: &ImageSize, focale: f64, samples: u32) -> Sampler {
+        assert!(samples > 0);
+        assert!(size.width > 0 && size.height > 0);
+        let scale = 1. / (size.width as f64);
 
         Sampler {
             focale,
-            max_x,
-            max_y,
-            fac_x: -1. / ((size.width - 1) as f64),
-            fac_y: -h / ((size.height - 1) as f64),
-            hlf_h: h / 2.,
+            scale,
+            hlf_h: size.height as f64 * scale / 2.,
+            size: *size,
+            window: Window::full(size),
+            samples,
+            pattern: Pattern::Regular,
+            s0: 0,
+            s1: samples,
             x: 0,
             y: 0,
+            s: 0,
         }
     }
 
-    fn convert(&self) -> Ray {
-        let x = self.fac_x * (self.x as f64) + 0.5;
-        let y = self.fac_y * (self.y as f64) + self.hlf_h;
+    // restrict to a crop window, a scanline or a tile
+    pub fn with_window(mut self, window: Window) -> Sampler {
+        assert!(window.x1 <= self.size.width && window.y1 <= self.size.height);
+        self.window = window;
+        self.x = window.x0;
+        self.y = window.y0;
+        self.s = self.s0;
+        self
+    }
+
+    // only samples s0 <= s < s1 of each pixel, as placed by the pattern for
+    // the full count: passes over consecutive ranges add up to all samples
+    pub fn with_sample_range(mut self, s0: u32, s1: u32) -> Sampler {
+        assert!(s0 < s1 && s1 <= self.samples);
+        self.s0 = s0;
+        self.s1 = s1;
+        let window = self.window;
+        self.with_window(window)
+    }
+
+    pub fn with_row(self, y: u32) -> Sampler {
+        let width = self.size.width;
+        self.with_window(Window::new(0, y, width, y + 1))
+    }
+
+    pub fn get_window(&self) -> &Window {
+        &self.window
+    }
+
+    pub fn with_pattern(mut self, pattern: Pattern) -> Sampler {
+        self.pattern = pattern;
+        self
+    }
 
-        Ray::new(Point::new(x, y, 0.), Vector::new(x, y, self.focale).unit()) // TODO: document FM
-                                                                              // ray
+    pub fn get_samples(&self) -> u32 {
+        self.samples
+    }
+
+    pub fn get_pattern(&self) -> Pattern {
+        self.pattern
+    }
+
+    fn convert(&self) -> Sample {
+        let (u, v) = self.pattern.position(self.x, self.y, self.s, self.samples);
+        let (dx, dy) = (u - 0.5, v - 0.5);
+
+        let x = 0.5 - self.scale * (self.x as f64 + 0.5 + dx);
+        let y = self.hlf_h - self.scale * (self.y as f64 + 0.5 + dy);
+
+        // TODO: document FM ray
+        let ray = Ray::new(Point::new(x, y, 0.), Vector::new(x, y, self.focale).unit());
+
+        Sample {
+            x: self.x,
+            y: self.y,
+            offset: (dx, dy),
+            ray,
+        }
     }
 }
 
 impl Iterator for Sampler {
-    type Item = (u32, u32, Ray);
+    type Item = Sample;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let ret;
-
-        if self.x == self.max_x && self.y == self.max_y {
-            ret = None;
-        } else if self.x == self.max_x {
-            ret = Some((self.x, self.y, self.convert()));
-            self.x = 0;
-            self.y += 1;
-        } else {
-            ret = Some((self.x, self.y, self.convert()));
+        if self.y == self.window.y1 {
+            return None;
+        }
+
+        let ret = self.convert();
+        self.s += 1;
+        if self.s == self.s1 {
+            self.s = self.s0;
             self.x += 1;
+            if self.x == self.window.x1 {
+                self.x = self.window.x0;
+                self.y += 1;
+            }
+        }
+        Some(ret)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let w = &self.window;
+        let pixels = (w.y1 - self.y) as usize * w.width() as usize - (self.x - w.x0) as usize;
+        let n = pixels * (self.s1 - self.s0) as usize - (self.s - self.s0) as usize;
+        (n, Some(n))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nearly_equal;
+
+    #[test]
+    fn every_pixel() {
+        let size = ImageSize::new(4, 3);
+        let samples: Vec<Sample> = Sampler::with_samples(&size, 1., 2).collect();
+        assert_eq!(samples.len(), 24);
+        let last = samples.last().unwrap();
+        assert_eq!((last.x, last.y), (3, 2));
+
+        // corner pixels are symmetric around the optical axis
+        let (first, last) = (&samples[0].ray.v, &samples[23].ray.v);
+        assert!(nearly_equal(first.x, -last.x) && nearly_equal(first.y, -last.y));
+    }
+
+    #[test]
+    fn one_pixel() {
+        let samples: Vec<Sample> = Sampler::new(&ImageSize::new(1, 1), 1.).collect();
+        assert_eq!(samples.len(), 1);
+        assert!(samples[0].ray.v.nearly_equal(&Vector::new(0., 0., 1.)));
+
+        let samples: Vec<Sample> = Sampler::new(&ImageSize::new(1, 5), 1.).collect();
+        assert_eq!(samples.len(), 5);
+        assert!(samples[2].ray.v.nearly_equal(&Vector::new(0., 0., 1.)));
+    }
+
+    #[test]
+    fn windows() {
+        let size = ImageSize::new(10, 7);
+        let window = Window::new(2, 3, 5, 5);
+        let sampler = Sampler::with_samples(&size, 1., 3).with_window(window);
+        assert_eq!(sampler.size_hint(), (18, Some(18)));
+        let samples: Vec<Sample> = sampler.collect();
+        assert_eq!(samples.len(), 18);
+        assert!(samples.iter().all(|s| window.contains(s.x, s.y)));
+        assert_eq!((samples[0].x, samples[0].y), (2, 3));
+
+        // same rays as the full image
+        let full: Vec<Sample> = Sampler::new(&size, 1.).collect();
+        let row: Vec<Sample> = Sampler::new(&size, 1.).with_row(4).collect();
+        assert_eq!(row.len(), 10);
+        assert!(row[7].ray.v.nearly_equal(&full[47].ray.v));
+
+        let mut count = vec![0; 70];
+        for tile in Window::full(&size).tiles(4) {
+            for s in Sampler::new(&size, 1.).with_window(tile) {
+                count[(s.y * 10 + s.x) as usize] += 1;
+            }
+        }
+        assert_eq!(count, vec![1; 70]);
+    }
+
+    #[test]
+    fn sample_ranges() {
+        let size = ImageSize::new(3, 2);
+        let all: Vec<Sample> = Sampler::with_samples(&size, 1., 8)
+            .with_pattern(Pattern::Sobol)
+            .collect();
+
+        // passes of 1, 1, 2 and 4 samples per pixel
+        let mut passes: Vec<Sample> = vec![];
+        for (s0, s1) in [(0, 1), (1, 2), (2, 4), (4, 8)] {
+            let sampler = Sampler::with_samples(&size, 1., 8)
+                .with_pattern(Pattern::Sobol)
+                .with_sample_range(s0, s1);
+            assert_eq!(sampler.size_hint().0, 6 * (s1 - s0) as usize);
+            passes.extend(sampler);
+        }
+        assert_eq!(passes.len(), all.len());
+        for s in &all {
+            assert!(passes
+                .iter()
+                .any(|p| (p.x, p.y, p.offset) == (s.x, s.y, s.offset)));
         }
-        ret
     }
 }
@@ -0,0 +1,335 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::{Aabb, Accumulator, Bvh, Camera, Color, Hit, Image, Ray, Shapes, Window};
+
+// tile side in pixels, tiles are the unit of work of the render threads
+pub const TILE_SIZE: u32 = 16;
+
+// state of a progressive render after a pass
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Progress {
+    pub pass: u32,
+    pub samples: u32, // samples per pixel taken so far
+    pub elapsed: Duration,
+}
+
+// Shapes are searched through a BVH built on their reference cs bounds:
+// camera rays are moved to reference cs for the traversal only.
+pub struct Renderer {
+    camera: Camera,
+    shapes: Vec<Box<dyn Shapes>>,
+    bvh: Bvh,
+    threads: usize,
+}
+
+impl Renderer {
+    // fails if the camera cs cannot be built
+    pub fn new(
+        mut camera: Camera,
+        mut shapes: Vec<Box<dyn Shapes>>,
+    ) -> Result<Renderer, &'static str> {
+        camera.update()?;
+        for shape in shapes.iter_mut() {
+            shape.compute_camcs_to_shapecs(&camera);
+        }
+        let bounds: Vec<Aabb> = shapes.iter().map(|s| s.bounds()).collect();
+
+        Ok(Renderer {
+            camera,
+            shapes,
+            bvh: Bvh::build(&bounds),
+            threads: thread::available_parallelism().map_or(1, |n| n.get()),
+        })
+    }
+
+    // number of render threads, defaults to the available parallelism
+    pub fn set_threads(&mut self, threads: usize) -> &mut Self {
+        assert!(threads > 0);
+        self.threads = threads;
+        self
+    }
+
+    pub fn get_threads(&self) -> usize {
+        self.threads
+    }
+
+    fn to_rcs(&self, ray: &Ray) -> Ray {
+        let m = self.camera.get_matrix_to_rcs();
+        Ray::new(m * &ray.o, m * &ray.v)
+    }
+
+    pub fn get_camera(&self) -> &Camera {
+        &self.camera
+    }
+
+    pub fn get_shapes(&self) -> &[Box<dyn Shapes>] {
+        &self.shapes
+    }
+
+    // closest hit along ray (camera cs) with the index of the shape hit
+    pub fn closest_hit(&self, ray: &Ray) -> Option<(usize, Hit)> {
+        // hits are kept along the traversal: shapes such as meshes know
+        // which part was hit only then
+        let mut best = None;
+        self.bvh
+            .closest(&self.to_rcs(ray), f64::INFINITY, |i, t_best| {
+                let hit = self.shapes[i].hit(ray).filter(|h| h.t < t_best)?;
+                let t = hit.t;
+                best = Some((i, hit));
+                Some(t)
+            });
+        best
+    }
+
+    // true as soon as one shape is crossed by ray for some t in [t_min, t_max]
+    pub fn occluded(&self, ray: &Ray, t_min: f64, t_max: f64) -> bool {
+        self.bvh.any(&self.to_rcs(ray), t_min, t_max, |i| {
+            self.shapes[i].any_hit(ray, t_min, t_max)
+        })
+    }
+
+    // shade is called once per camera ray, with the closest hit if any.
+    // Samples are weighted into pixels by the camera reconstruction filter.
+    pub fn render<F>(&self, shade: F) -> Image
+    where
+        F: Fn(&Ray, Option<(usize, &Hit)>) -> Color + Sync,
+    {
+        let mut acc = self.accumulator();
+        self.render_pass(&mut acc, 0, self.camera.get_samples(), &shade);
+        acc.to_image()
+    }
+
+    // Renders in passes of 1, 1, 2, 4... samples per pixel until the camera
+    // samples are all taken or budget has elapsed, passes are shortened to
+    // fit the remaining budget. With a budget, camera samples may be u32::MAX
+    // for no target. preview is called with the image after each pass.
+    // Patterns place samples for the full count: only progressive ones (see
+    // Pattern::is_progressive) give even previews and truncated renders.
+    pub fn render_progressive<F, P>(
+        &self,
+        shade: F,
+        budget: Option<Duration>,
+        mut preview: P,
+    ) -> Image
+    where
+        F: Fn(&Ray, Option<(usize, &Hit)>) -> Color + Sync,
+        P: FnMut(&Image, &Progress),
+    {
+        let start = Instant::now();
+        let target = self.camera.get_samples();
+        let mut acc = self.accumulator();
+        let mut progress = Progress::default();
+
+        while progress.samples < target {
+            let mut count = progress.samples.max(1).min(target - progress.samples);
+            if let Some(budget) = budget.filter(|_| progress.samples > 0) {
+                if progress.elapsed >= budget {
+                    break;
+                }
+                let per_sample = progress.elapsed.as_secs_f64() / progress.samples as f64;
+                let left = (budget - progress.elapsed).as_secs_f64() / per_sample;
+                count = count.min(left as u32).max(1);
+            }
+
+            self.render_pass(&mut acc, progress.samples, progress.samples + count, &shade);
+            progress.pass += 1;
+            progress.samples += count;
+            progress.elapsed = start.elapsed();
+            preview(&acc.to_image(), &progress);
+        }
+        acc.to_image()
+    }
+
+    fn accumulator(&self) -> Accumulator {
+        let (filter, radius) = self.camera.get_filter();
+        Accumulator::new(self.camera.get_image_size(), filter, radius)
+    }
+
+    // Samples s0 <= s < s1 of every pixel. Tiles are rendered by a pool of
+    // threads into their own accumulators, merged in tile order: the image
+    // does not depend on the thread count.
+    fn render_pass<F>(&self, acc: &mut Accumulator, s0: u32, s1: u32, shade: &F)
+    where
+        F: Fn(&Ray, Option<(usize, &Hit)>) -> Color + Sync,
+    {
+        let tiles = self.camera.sampler().get_window().tiles(TILE_SIZE);
+        let done: Vec<Mutex<Option<Accumulator>>> =
+            tiles.iter().map(|_| Mutex::new(None)).collect();
+        let next = AtomicUsize::new(0);
+
+        thread::scope(|scope| {
+            for _ in 0..self.threads.min(tiles.len()) {
+                scope.spawn(|| loop {
+                    let i = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(tile) = tiles.get(i) else { break };
+                    let tile_acc = self.render_tile(tile, s0, s1, acc, shade);
+                    *done[i].lock().unwrap() = Some(tile_acc);
+                });
+            }
+        });
+
+        for tile_acc in done {
+            acc.merge(&tile_acc.into_inner().unwrap().unwrap());
+        }
+    }
+
+    // samples of tile, splat into the pixels they reach
+    fn render_tile<F>(
+        &self,
+        tile: &Window,
+        s0: u32,
+        s1: u32,
+        acc: &Accumulator,
+        shade: &F,
+    ) -> Accumulator
+    where
+        F: Fn(&Ray, Option<(usize, &Hit)>) -> Color,
+    {
+        let mut tile_acc = Accumulator::with_window(
+            acc.get_size(),
+            &acc.reach(tile),
+            acc.get_filter(),
+            acc.get_radius(),
+        );
+
+        let sampler = self.camera.sampler().with_window(*tile);
+        for sample in sampler.with_sample_range(s0, s1) {
+            let hit = self.closest_hit(&sample.ray);
+            let c = shade(&sample.ray, hit.as_ref().map(|(i, h)| (*i, h)));
+            tile_acc.add(&sample, &c);
+        }
+        tile_acc
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Ball, Cs, Filter, Pattern, Point, Vector, BLACK, WHITE};
+
+    fn ball_at(z: f64, radius: f64) -> Box<dyn Shapes> {
+        let mut ball = Ball::build(radius);
+        let mut cs = Cs::new();
+        cs.translate(&Vector::new(0., 0., z));
+        ball.set_shape_cs(cs);
+        Box::new(ball)
+    }
+
+    #[test]
+    fn closest_1() {
+        let renderer =
+            Renderer::new(Camera::new(), vec![ball_at(20., 1.), ball_at(10., 1.)]).unwrap();
+        let ray = Ray::new(Point::new(0., 0., 0.), Vector::new(0., 0., 1.));
+
+        let (i, hit) = renderer.closest_hit(&ray).unwrap();
+        assert_eq!(i, 1);
+        assert!(hit.point.nearly_equal(&Point::new(0., 0., 9.)));
+    }
+
+    #[test]
+    fn occluded_1() {
+        let renderer = Renderer::new(Camera::new(), vec![ball_at(10., 1.)]).unwrap();
+        let ray = Ray::new(Point::new(0., 0., 0.), Vector::new(0., 0., 1.));
+
+        assert!(renderer.occluded(&ray, 0., 20.));
+        assert!(renderer.occluded(&ray, 10., 20.));
+        assert!(!renderer.occluded(&ray, 0., 8.5));
+        assert!(!renderer.occluded(&ray, 11.5, f64::INFINITY));
+    }
+
+    #[test]
+    fn render_1() {
+        let mut cam = Camera::new();
+        cam.set_image_size(11, 11);
+        let renderer = Renderer::new(cam, vec![ball_at(10., 1.)]).unwrap();
+
+        let img = renderer.render(|_, hit| if hit.is_some() { WHITE } else { BLACK });
+        assert_eq!(*img.get(5, 5), WHITE);
+        assert_eq!(*img.get(0, 0), BLACK);
+    }
+
+    #[test]
+    fn render_2() {
+        let mut cam = Camera::new();
+        cam.set_image_size(11, 11).set_samples(4);
+        let renderer = Renderer::new(cam, vec![ball_at(10., 1.)]).unwrap();
+
+        let img = renderer.render(|_, _| WHITE);
+        assert!(img.get(5, 5).nearly_equal(&WHITE));
+    }
+
+    #[test]
+    fn render_filter() {
+        let mut cam = Camera::new();
+        cam.set_image_size(11, 11)
+            .set_samples(4)
+            .set_filter(Filter::Mitchell, 2.);
+        let renderer = Renderer::new(cam, vec![ball_at(10., 1.)]).unwrap();
+
+        // negative lobes cancel out on a flat image
+        let img = renderer.render(|_, _| WHITE);
+        assert!(img.get(5, 5).nearly_equal(&WHITE));
+        assert!(img.get(0, 5).nearly_equal(&WHITE));
+
+        // the ball edge is blurred over its neighbours
+        let img = renderer.render(|_, hit| if hit.is_some() { WHITE } else { BLACK });
+        assert!(img.get(5, 5).r > 0.9);
+        assert!(img.get(0, 0).nearly_equal(&BLACK));
+    }
+
+    #[test]
+    fn render_threads() {
+        let mut cam = Camera::new();
+        cam.set_image_size(50, 37)
+            .set_samples(3)
+            .set_pattern(Pattern::Jittered)
+            .set_filter(Filter::Gaussian, 1.5);
+        let mut renderer = Renderer::new(cam, vec![ball_at(10., 3.)]).unwrap();
+        let shade = |ray: &Ray, hit: Option<(usize, &Hit)>| match hit {
+            Some((_, h)) => Color::new(h.normal.x.abs(), h.normal.y.abs(), ray.v.z),
+            None => BLACK,
+        };
+
+        let img = renderer.set_threads(1).render(shade);
+        for threads in [2, 3, 8] {
+            let other = renderer.set_threads(threads).render(shade);
+            assert_eq!(other.pixels(), img.pixels());
+        }
+    }
+
+    #[test]
+    fn render_progressive() {
+        let mut cam = Camera::new();
+        cam.set_image_size(20, 20)
+            .set_samples(11)
+            .set_pattern(Pattern::Halton)
+            .set_filter(Filter::Tent, 1.);
+        let renderer = Renderer::new(cam, vec![ball_at(10., 3.)]).unwrap();
+        let shade = |_: &Ray, hit: Option<(usize, &Hit)>| if hit.is_some() { WHITE } else { BLACK };
+
+        let mut passes = vec![];
+        let img = renderer.render_progressive(shade, None, |img, p| {
+            assert_eq!(p.pass as usize, passes.len() + 1);
+            passes.push(p.samples);
+            assert!(img.get(10, 10).nearly_equal(&WHITE));
+        });
+        assert_eq!(passes, [1, 2, 4, 8, 11]);
+
+        // same samples as a single pass, summed in another order
+        let full = renderer.render(shade);
+        for (a, b) in img.pixels().iter().zip(full.pixels()) {
+            assert!(a.nearly_equal(b));
+        }
+
+        // a spent budget still renders one pass
+        let mut count = 0;
+        renderer.render_progressive(shade, Some(Duration::ZERO), |_, p| {
+            count += 1;
+            assert_eq!(p.samples, 1);
+        });
+        assert_eq!(count, 1);
+    }
+}
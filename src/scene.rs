@@ -0,0 +1,46 @@
+use std::fmt::Display;
+
+use crate::{Camera, Light, Material, Shapes};
+
+// Scene built from its text description, see parser.rs for the format.
+// materials[i] is the material of shapes[i].
+pub struct Scene {
+    pub camera: Camera,
+    pub shapes: Vec<Box<dyn Shapes>>,
+    pub materials: Vec<Material>,
+    pub lights: Vec<Light>,
+}
+
+impl Scene {
+    pub fn parse(src: &str) -> Result<Scene, ParseError> {
+        parser::Parser::new(lexer::tokenize(src)?).parse_scene()
+    }
+}
+
+#[derive(Debug)]
+pub struct ParseError {
+    pub line: usize,
+    pub col: usize,
+    pub msg: String,
+}
+
+impl ParseError {
+    pub fn new(line: usize, col: usize, msg: &str) -> ParseError {
+        ParseError {
+            line,
+            col,
+            msg: msg.to_owned(),
+        }
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}, column {}: {}", self.line, self.col, self.msg)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+mod lexer;
+mod parser;
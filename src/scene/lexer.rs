@@ -0,0 +1,172 @@
+use super::ParseError;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Token {
+    Ident(String),
+    Number(f64),
+    LBrace,
+    RBrace,
+    Lt,
+    Gt,
+    Comma,
+    Eof,
+}
+
+impl std::fmt::Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Token::Ident(s) => write!(f, "'{s}'"),
+            Token::Number(k) => write!(f, "number {k}"),
+            Token::LBrace => write!(f, "'{{'"),
+            Token::RBrace => write!(f, "'}}'"),
+            Token::Lt => write!(f, "'<'"),
+            Token::Gt => write!(f, "'>'"),
+            Token::Comma => write!(f, "','"),
+            Token::Eof => write!(f, "end of file"),
+        }
+    }
+}
+
+pub struct Spanned {
+    pub token: Token,
+    pub line: usize,
+    pub col: usize,
+}
+
+pub fn tokenize(src: &str) -> Result<Vec<Spanned>, ParseError> {
+    let mut tokens = vec![];
+    let mut chars = src.chars().peekable();
+    let (mut line, mut col) = (1, 1);
+
+    while let Some(&c) = chars.peek() {
+        let (tok_line, tok_col) = (line, col);
+        let token = match c {
+            '\n' => {
+                chars.next();
+                line += 1;
+                col = 1;
+                continue;
+            }
+            c if c.is_whitespace() => {
+                chars.next();
+                col += 1;
+                continue;
+            }
+            '#' => {
+                while chars.peek().is_some_and(|c| *c != '\n') {
+                    chars.next();
+                }
+                continue;
+            }
+            '{' | '}' | '<' | '>' | ',' => {
+                chars.next();
+                col += 1;
+                match c {
+                    '{' => Token::LBrace,
+                    '}' => Token::RBrace,
+                    '<' => Token::Lt,
+                    '>' => Token::Gt,
+                    _ => Token::Comma,
+                }
+            }
+            c if c.is_ascii_digit() || c == '-' || c == '+' || c == '.' => {
+                let mut s = String::new();
+                while let Some(&c) = chars.peek() {
+                    let sign = (c == '-' || c == '+') && (s.is_empty() || s.ends_with(['e', 'E']));
+                    if c.is_ascii_digit() || c == '.' || c == 'e' || c == 'E' || sign {
+                        s.push(c);
+                    } else {
+                        break;
+                    }
+                    chars.next();
+                    col += 1;
+                }
+                match s.parse::<f64>() {
+                    Ok(k) => Token::Number(k),
+                    Err(_) => {
+                        return Err(ParseError::new(
+                            tok_line,
+                            tok_col,
+                            &format!("invalid number '{s}'"),
+                        ))
+                    }
+                }
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let mut s = String::new();
+                while let Some(&c) = chars.peek() {
+                    if c.is_alphanumeric() || c == '_' {
+                        s.push(c);
+                        chars.next();
+                        col += 1;
+                    } else {
+                        break;
+                    }
+                }
+                Token::Ident(s)
+            }
+            c => {
+                return Err(ParseError::new(
+                    line,
+                    col,
+                    &format!("unexpected character '{c}'"),
+                ))
+            }
+        };
+
+        tokens.push(Spanned {
+            token,
+            line: tok_line,
+            col: tok_col,
+        });
+    }
+
+    tokens.push(Spanned {
+        token: Token::Eof,
+        line,
+        col,
+    });
+    Ok(tokens)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tokens_1() {
+        let tokens = tokenize("ball { # comment\n  radius -1.5e1 <1,2,3> }").unwrap();
+        let kinds: Vec<Token> = tokens.iter().map(|t| t.token.clone()).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                Token::Ident("ball".to_owned()),
+                Token::LBrace,
+                Token::Ident("radius".to_owned()),
+                Token::Number(-15.),
+                Token::Lt,
+                Token::Number(1.),
+                Token::Comma,
+                Token::Number(2.),
+                Token::Comma,
+                Token::Number(3.),
+                Token::Gt,
+                Token::RBrace,
+                Token::Eof,
+            ]
+        );
+        assert_eq!((tokens[2].line, tokens[2].col), (2, 3));
+    }
+
+    #[test]
+    fn tokens_2() {
+        let e = tokenize("ball {\n  radius 1..2 }").err().unwrap();
+        assert_eq!((e.line, e.col), (2, 10));
+    }
+
+    #[test]
+    fn tokens_3() {
+        let e = tokenize("ball @").err().unwrap();
+        assert_eq!((e.line, e.col), (1, 6));
+    }
+}
@@ -0,0 +1,601 @@
+// Scene description format:
+//
+//   # comment until end of line
+//   camera {
+//       location <0, 2, -10>
+//       look_at <0, 0, 0>
+//       angle 40                  # horizontal field of view in degrees
+//       focale 1.2                # or focal length
+//       size 640 480              # at most 8192x8192 pixels
+//   }
+//
+//   material red {
+//       color <1, 0, 0>
+//       ambient 0.1 diffuse 0.9 specular 0.3 shininess 32
+//       reflection 0 transparency 0 ior 1
+//   }
+//
+//   point_light { position <10, 10, -10> color <1, 1, 1> }
+//   directional_light { direction <0, -1, 0> color <0.2, 0.2, 0.2> }
+//   spot_light { position <0, 5, 0> direction <0, -1, 0> angle 20 exponent 2 }
+//
+//   ball { radius 1 translate <0, 1, 0> material red }
+//   cylinder { radius 0.5 rotate_x 90 material { color <0, 1, 0> } }
+//   cylinder { radius 0.5 height 2 }  # capped, add 'open' for a tube
+//   disk { radius 1 }                 # in plane y = 0
+//   plane { translate <0, -1, 0> }    # y = 0, solid below for CSG
+//   cone { angle 30 height 2 }        # apex at origin, opens along +y,
+//                                     # infinite double cone without height
+//   cuboid { min <-1, -1, -1> max <1, 1, 1> }
+//   torus { major 2 minor 0.5 }       # around the y axis
+//   difference {
+//       ball { radius 2 }
+//       cylinder { radius 0.5 }
+//       scale 2
+//       material red
+//   }
+//
+// Shape transforms are applied in order:
+//   translate <x, y, z>
+//   rotate_x a, rotate_y a, rotate_z a (degrees)
+//   rotate <ax, ay, az>               # around X, then Y, then Z
+//   rotate_axis <x, y, z> a
+//   scale s, scale <sx, sy, sz>
+// CSG nodes (union, intersection, difference) take exactly two shapes.
+
+use std::collections::HashMap;
+
+use super::lexer::{Spanned, Token};
+use super::{ParseError, Scene};
+use crate::MAX_PIXELS;
+use crate::{Ball, Camera, Color, Cone, Cs, Csg, CsgOp, Cuboid, Cylinder, Disk, EulerOrder};
+use crate::{Focale, ImageSize, Light, Material, Plane, Point, Shapes, Torus, Vector};
+
+pub struct Parser {
+    tokens: Vec<Spanned>,
+    pos: usize,
+    materials: HashMap<String, Material>,
+}
+
+impl Parser {
+    pub fn new(tokens: Vec<Spanned>) -> Parser {
+        Parser {
+            tokens,
+            pos: 0,
+            materials: HashMap::new(),
+        }
+    }
+
+    pub fn parse_scene(mut self) -> Result<Scene, ParseError> {
+        let mut scene = Scene {
+            camera: Camera::new(),
+            shapes: vec![],
+            materials: vec![],
+            lights: vec![],
+        };
+
+        loop {
+            let ident = match &self.peek().token {
+                Token::Eof => break,
+                Token::Ident(s) => s.clone(),
+                t => return Err(self.error(&format!("unexpected {t}"))),
+            };
+
+            match ident.as_str() {
+                "camera" => {
+                    self.next();
+                    self.parse_camera(&mut scene.camera)?;
+                }
+                "material" => {
+                    self.next();
+                    let name = self.ident()?;
+                    let material = self.parse_material()?;
+                    self.materials.insert(name, material);
+                }
+                "point_light" | "directional_light" | "spot_light" => {
+                    self.next();
+                    scene.lights.push(self.parse_light(&ident)?);
+                }
+                _ => {
+                    let (shape, material) = self.parse_shape()?;
+                    scene.shapes.push(shape);
+                    scene.materials.push(material.unwrap_or_default());
+                }
+            }
+        }
+        Ok(scene)
+    }
+
+    fn parse_camera(&mut self, camera: &mut Camera) -> Result<(), ParseError> {
+        let (line, col) = self.position_prev();
+        self.expect(Token::LBrace)?;
+        while !self.eat(&Token::RBrace) {
+            match self.ident()?.as_str() {
+                "location" => {
+                    camera.move_to(self.point()?);
+                }
+                "look_at" => {
+                    camera.look_at(self.point()?);
+                }
+                "angle" => {
+                    let angle = self.number()?;
+                    if angle <= 0. || angle >= 180. {
+                        return Err(self.error_prev("angle must be in (0, 180) degrees"));
+                    }
+                    camera.set_focale(Focale::AngleDeg(angle));
+                }
+                "focale" => {
+                    let focale = self.positive()?;
+                    camera.set_focale(Focale::Focale(focale));
+                }
+                "size" => {
+                    let width = self.size()?;
+                    let height = self.size()?;
+                    if ImageSize::new(width, height).is_too_large() {
+                        return Err(self.error_prev(&format!(
+                            "image {width}x{height} is larger than {MAX_PIXELS} pixels"
+                        )));
+                    }
+                    camera.set_image_size(width, height);
+                }
+                s => return Err(self.error_prev(&format!("unknown camera property '{s}'"))),
+            }
+        }
+        if camera.get_location().nearly_equal(camera.get_look_at()) {
+            return Err(ParseError::new(
+                line,
+                col,
+                "camera looks at its own location",
+            ));
+        }
+        Ok(())
+    }
+
+    fn parse_material(&mut self) -> Result<Material, ParseError> {
+        let mut material = Material::default();
+
+        self.expect(Token::LBrace)?;
+        while !self.eat(&Token::RBrace) {
+            match self.ident()?.as_str() {
+                "color" => material.color = self.color()?,
+                "ambient" => material.ambient = self.non_negative()?,
+                "diffuse" => material.diffuse = self.non_negative()?,
+                "specular" => material.specular = self.non_negative()?,
+                "shininess" => material.shininess = self.non_negative()?,
+                "reflection" => material.reflection = self.non_negative()?,
+                "transparency" => material.transparency = self.non_negative()?,
+                "ior" => material.ior = self.positive()?,
+                s => return Err(self.error_prev(&format!("unknown material property '{s}'"))),
+            }
+        }
+        Ok(material)
+    }
+
+    fn parse_light(&mut self, kind: &str) -> Result<Light, ParseError> {
+        let mut position = Point::new(0., 0., 0.);
+        let mut direction = Vector::new(0., -1., 0.);
+        let mut angle = 30.;
+        let mut exponent = 1.;
+        let mut color = Color::new(1., 1., 1.);
+
+        self.expect(Token::LBrace)?;
+        while !self.eat(&Token::RBrace) {
+            match (kind, self.ident()?.as_str()) {
+                ("point_light" | "spot_light", "position") => position = self.point()?,
+                ("directional_light" | "spot_light", "direction") => {
+                    direction = self.vector()?;
+                    if direction.nearly_zero() {
+                        return Err(self.error_prev("direction must not be a null vector"));
+                    }
+                }
+                ("spot_light", "angle") => {
+                    angle = self.positive()?;
+                    if angle >= 90. {
+                        return Err(self.error_prev("spot angle must be in (0, 90) degrees"));
+                    }
+                }
+                ("spot_light", "exponent") => exponent = self.non_negative()?,
+                (_, "color") => color = self.color()?,
+                (_, s) => return Err(self.error_prev(&format!("unknown {kind} property '{s}'"))),
+            }
+        }
+
+        Ok(match kind {
+            "point_light" => Light::Point { position, color },
+            "directional_light" => Light::Directional { direction, color },
+            _ => Light::Spot {
+                position,
+                direction,
+                angle,
+                exponent,
+                color,
+            },
+        })
+    }
+
+    fn parse_shape(&mut self) -> Result<(Box<dyn Shapes>, Option<Material>), ParseError> {
+        let kind = self.ident()?;
+        let op = match kind.as_str() {
+            "ball" | "cylinder" | "disk" | "plane" | "cone" | "cuboid" | "torus" => None,
+            "union" => Some(CsgOp::Union),
+            "intersection" => Some(CsgOp::Intersection),
+            "difference" => Some(CsgOp::Difference),
+            s => return Err(self.error_prev(&format!("unknown keyword '{s}'"))),
+        };
+        let (line, col) = self.position_prev();
+
+        let mut radius = 1.;
+        let mut height = None;
+        let mut open = false;
+        let mut angle = 45.;
+        let (mut min, mut max) = (Point::new(-1., -1., -1.), Point::new(1., 1., 1.));
+        let (mut major, mut minor) = (1., 0.25);
+        let mut cs = Cs::new();
+        let mut material = None;
+        let mut children = vec![];
+
+        self.expect(Token::LBrace)?;
+        while !self.eat(&Token::RBrace) {
+            let prop = self.ident()?;
+            match prop.as_str() {
+                "translate" => cs.translate(&self.vector()?),
+                "rotate_x" => cs.rotate_x(self.number()?),
+                "rotate_y" => cs.rotate_y(self.number()?),
+                "rotate_z" => cs.rotate_z(self.number()?),
+                "rotate" => {
+                    let (x, y, z) = self.triple()?;
+                    cs.rotate_euler(x, y, z, EulerOrder::XYZ);
+                }
+                "rotate_axis" => {
+                    let axis = self.vector()?;
+                    if axis.nearly_zero() {
+                        return Err(self.error_prev("rotation axis must not be a null vector"));
+                    }
+                    cs.rotate_axis(&axis, self.number()?);
+                }
+                "scale" => {
+                    if self.peek().token == Token::Lt {
+                        let (x, y, z) = self.triple()?;
+                        if x <= 0. || y <= 0. || z <= 0. {
+                            return Err(self.error_prev("scale factors must be positive"));
+                        }
+                        cs.scale_xyz(x, y, z);
+                    } else {
+                        cs.scale(self.positive()?);
+                    }
+                }
+                "material" => {
+                    material = Some(if self.peek().token == Token::LBrace {
+                        self.parse_material()?
+                    } else {
+                        let name = self.ident()?;
+                        match self.materials.get(&name) {
+                            Some(m) => m.clone(),
+                            None => {
+                                return Err(self.error_prev(&format!("undefined material '{name}'")))
+                            }
+                        }
+                    })
+                }
+                "radius" if matches!(kind.as_str(), "ball" | "cylinder" | "disk") => {
+                    radius = self.positive()?
+                }
+                "height" if kind == "cylinder" || kind == "cone" => height = Some(self.positive()?),
+                "open" if kind == "cylinder" || kind == "cone" => open = true,
+                "angle" if kind == "cone" => {
+                    angle = self.positive()?;
+                    if angle >= 90. {
+                        return Err(self.error_prev("cone angle must be below 90 degrees"));
+                    }
+                }
+                "min" if kind == "cuboid" => min = self.point()?,
+                "max" if kind == "cuboid" => max = self.point()?,
+                "major" if kind == "torus" => major = self.positive()?,
+                "minor" if kind == "torus" => minor = self.positive()?,
+                _ if op.is_some() => {
+                    self.pos -= 1;
+                    let (pos_line, pos_col) = (self.peek().line, self.peek().col);
+                    let (child, child_material) = self.parse_shape()?;
+                    if child_material.is_some() {
+                        return Err(ParseError::new(
+                            pos_line,
+                            pos_col,
+                            "material is not allowed on CSG operands",
+                        ));
+                    }
+                    children.push(child);
+                }
+                s => return Err(self.error_prev(&format!("unknown {kind} property '{s}'"))),
+            }
+        }
+
+        let mut shape: Box<dyn Shapes> = match op {
+            None if kind == "ball" => Box::new(Ball::build(radius)),
+            None if kind == "disk" => Box::new(Disk::build(radius)),
+            None if kind == "plane" => Box::new(Plane::build()),
+            None if kind == "cuboid" => {
+                if min.x >= max.x || min.y >= max.y || min.z >= max.z {
+                    return Err(ParseError::new(line, col, "cuboid min must be below max"));
+                }
+                Box::new(Cuboid::build(min, max))
+            }
+            None if kind == "torus" => {
+                if minor >= major {
+                    return Err(ParseError::new(
+                        line,
+                        col,
+                        "torus minor must be below major",
+                    ));
+                }
+                Box::new(Torus::build(major, minor))
+            }
+            None => match height {
+                _ if open && height.is_none() => {
+                    return Err(self.error_prev(&format!("open {kind} needs a height")))
+                }
+                Some(h) if kind == "cone" => Box::new(Cone::build(angle).with_height(h, !open)),
+                None if kind == "cone" => Box::new(Cone::build(angle)),
+                Some(h) => Box::new(Cylinder::build(radius).with_height(h, !open)),
+                None => Box::new(Cylinder::build(radius)),
+            },
+            Some(op) => {
+                if children.len() != 2 {
+                    return Err(ParseError::new(
+                        line,
+                        col,
+                        &format!("{kind} needs exactly 2 shapes, got {}", children.len()),
+                    ));
+                }
+                let b = children.pop().unwrap();
+                let a = children.pop().unwrap();
+                Box::new(Csg::build(op, a, b))
+            }
+        };
+        shape.set_shape_cs(cs);
+
+        Ok((shape, material))
+    }
+
+    fn peek(&self) -> &Spanned {
+        &self.tokens[self.pos]
+    }
+
+    fn next(&mut self) -> &Spanned {
+        let tok = &self.tokens[self.pos];
+        if tok.token != Token::Eof {
+            self.pos += 1;
+        }
+        tok
+    }
+
+    fn eat(&mut self, token: &Token) -> bool {
+        if self.peek().token == *token {
+            self.next();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, token: Token) -> Result<(), ParseError> {
+        if self.eat(&token) {
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected {token}, found {}", self.peek().token)))
+        }
+    }
+
+    fn ident(&mut self) -> Result<String, ParseError> {
+        match &self.peek().token {
+            Token::Ident(s) => {
+                let s = s.clone();
+                self.next();
+                Ok(s)
+            }
+            t => Err(self.error(&format!("expected keyword, found {t}"))),
+        }
+    }
+
+    fn number(&mut self) -> Result<f64, ParseError> {
+        match self.peek().token {
+            Token::Number(k) => {
+                self.next();
+                Ok(k)
+            }
+            ref t => Err(self.error(&format!("expected number, found {t}"))),
+        }
+    }
+
+    fn positive(&mut self) -> Result<f64, ParseError> {
+        let k = self.number()?;
+        if k > 0. {
+            Ok(k)
+        } else {
+            Err(self.error_prev(&format!("expected positive number, found {k}")))
+        }
+    }
+
+    fn non_negative(&mut self) -> Result<f64, ParseError> {
+        let k = self.number()?;
+        if k >= 0. {
+            Ok(k)
+        } else {
+            Err(self.error_prev(&format!("expected non negative number, found {k}")))
+        }
+    }
+
+    fn size(&mut self) -> Result<u32, ParseError> {
+        let k = self.number()?;
+        if k >= 1. && k.fract() == 0. && k <= u32::MAX as f64 {
+            Ok(k as u32)
+        } else {
+            Err(self.error_prev(&format!("expected image size, found {k}")))
+        }
+    }
+
+    fn triple(&mut self) -> Result<(f64, f64, f64), ParseError> {
+        self.expect(Token::Lt)?;
+        let x = self.number()?;
+        self.expect(Token::Comma)?;
+        let y = self.number()?;
+        self.expect(Token::Comma)?;
+        let z = self.number()?;
+        self.expect(Token::Gt)?;
+        Ok((x, y, z))
+    }
+
+    fn point(&mut self) -> Result<Point, ParseError> {
+        let (x, y, z) = self.triple()?;
+        Ok(Point::new(x, y, z))
+    }
+
+    fn vector(&mut self) -> Result<Vector, ParseError> {
+        let (x, y, z) = self.triple()?;
+        Ok(Vector::new(x, y, z))
+    }
+
+    fn color(&mut self) -> Result<Color, ParseError> {
+        let (r, g, b) = self.triple()?;
+        Ok(Color::new(r, g, b))
+    }
+
+    fn position_prev(&self) -> (usize, usize) {
+        let tok = &self.tokens[self.pos.saturating_sub(1)];
+        (tok.line, tok.col)
+    }
+
+    // error located at the next token
+    fn error(&self, msg: &str) -> ParseError {
+        ParseError::new(self.peek().line, self.peek().col, msg)
+    }
+
+    // error located at the last consumed token
+    fn error_prev(&self, msg: &str) -> ParseError {
+        let (line, col) = self.position_prev();
+        ParseError::new(line, col, msg)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{nearly_equal, Color, Light, Point, Ray, Renderer, Scene, Vector};
+
+    const SCENE: &str = "
+# test scene
+camera {
+    location <0, 0, -10>
+    look_at <0, 0, 0>
+    angle 60
+    size 32 24
+}
+
+material red { color <1, 0, 0> shininess 8 }
+material glass { transparency 0.9 ior 1.5 }
+
+point_light { position <10, 10, -10> color <1, 1, 1> }
+spot_light { position <0, 5, 0> direction <0, -1, 0> angle 20 }
+
+ball { radius 1 scale <2, 2, 2> rotate <0, 90, 0> translate <0, 0, 5> material red }
+difference {
+    ball { radius 2 }
+    cylinder { radius 0.5 rotate_x 90 }
+    translate <5, 0, 0>
+    material { color <0, 0, 1> }
+}
+plane { translate <0, -5, 0> }
+cylinder { radius 1 height 2 translate <-5, 0, 0> }
+cone { angle 30 height 2 open translate <0, 5, 0> }
+cuboid { min <-1, -1, -1> max <1, 2, 3> translate <5, 5, 0> }
+torus { major 2 minor 0.5 rotate_x 90 translate <-5, 5, 0> }
+";
+
+    #[test]
+    fn parse_1() {
+        let scene = Scene::parse(SCENE).unwrap();
+        assert_eq!(scene.shapes.len(), 7);
+        assert_eq!(scene.materials[0].color, Color::new(1., 0., 0.));
+        assert_eq!(scene.materials[0].shininess, 8.);
+        assert_eq!(scene.materials[0].ior, 1.);
+        assert_eq!(scene.materials[1].color, Color::new(0., 0., 1.));
+        assert_eq!(scene.lights.len(), 2);
+        match &scene.lights[0] {
+            Light::Point { position, .. } => {
+                assert!(position.nearly_equal(&Point::new(10., 10., -10.)))
+            }
+            _ => panic!("point light expected"),
+        }
+        assert!(matches!(scene.lights[1], Light::Spot { angle, .. } if angle == 20.));
+        assert_eq!(scene.camera.get_image_size().width, 32);
+    }
+
+    #[test]
+    fn parse_2() {
+        let scene = Scene::parse(SCENE).unwrap();
+        let renderer = Renderer::new(scene.camera, scene.shapes).unwrap();
+
+        // camera looks along world z from z=-10: ball surface at world z=3
+        let ray = Ray::new(Point::new(0., 0., 0.), Vector::new(0., 0., 1.));
+        let (i, hit) = renderer.closest_hit(&ray).unwrap();
+        assert_eq!(i, 0);
+        assert!(nearly_equal(hit.world_point.z, 3.));
+
+        // drilled ball: the ray goes through the hole
+        let ray = Ray::new(Point::new(5., 0., 0.), Vector::new(0., 0., 1.));
+        assert!(renderer.closest_hit(&ray).is_none());
+
+        // ground plane, capped cylinder
+        let ray = Ray::new(Point::new(0., 0., 0.), Vector::new(0., -1., 0.));
+        let (i, hit) = renderer.closest_hit(&ray).unwrap();
+        assert_eq!(i, 2);
+        assert!(nearly_equal(hit.world_point.y, -5.));
+        let ray = Ray::new(Point::new(-5., 0., 0.), Vector::new(0., 0., 1.));
+        let (i, hit) = renderer.closest_hit(&ray).unwrap();
+        assert_eq!(i, 3);
+        assert!(nearly_equal(hit.world_point.z, -1.));
+
+        let ray = Ray::new(Point::new(5., 5., 0.), Vector::new(0., 0., 1.));
+        let (i, hit) = renderer.closest_hit(&ray).unwrap();
+        assert_eq!(i, 5);
+        assert!(nearly_equal(hit.world_point.z, -1.));
+        let ray = Ray::new(Point::new(-3., 5., 0.), Vector::new(0., 0., 1.));
+        let (i, hit) = renderer.closest_hit(&ray).unwrap();
+        assert_eq!(i, 6);
+        assert!(nearly_equal(hit.world_point.z, -0.5));
+    }
+
+    fn error_at(src: &str) -> (usize, usize) {
+        let e = Scene::parse(src).err().unwrap();
+        (e.line, e.col)
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(error_at("ball { radius -1 }"), (1, 15));
+        assert_eq!(error_at("ball {\n radius 1\n material blue }"), (3, 11));
+        assert_eq!(error_at("camera { angle 180 }"), (1, 16));
+        assert_eq!(
+            error_at("camera { location <1, 2, 3> look_at <1, 2, 3> }"),
+            (1, 1)
+        );
+        assert_eq!(error_at("camera { look_at <0, 0, 0> }"), (1, 1));
+        assert_eq!(error_at("camera { size 70000 70000 }"), (1, 21));
+        assert!(Scene::parse("camera { size 8192 8192 }").is_ok());
+        assert_eq!(error_at("prism { }"), (1, 1));
+        assert_eq!(error_at("union { ball { } }"), (1, 1));
+        assert_eq!(error_at("union { ball { material { } } ball { } }"), (1, 9));
+        assert_eq!(error_at("ball { translate <1, 2> }"), (1, 23));
+        assert_eq!(error_at("ball {"), (1, 7));
+        assert_eq!(error_at("point_light { angle 10 }"), (1, 15));
+        assert_eq!(error_at("material m { ior 0 }"), (1, 18));
+        assert_eq!(error_at("ball { scale <1, 0, 1> }"), (1, 22));
+        assert_eq!(error_at("ball { rotate_axis <0, 0, 0> 10 }"), (1, 28));
+        assert_eq!(error_at("spot_light { direction <0, 0, 0> }"), (1, 32));
+        assert_eq!(error_at("plane { radius 1 }"), (1, 9));
+        assert_eq!(error_at("ball { height 1 }"), (1, 8));
+        assert_eq!(error_at("cylinder { open }"), (1, 17));
+        assert_eq!(error_at("cone { angle 90 }"), (1, 14));
+        assert_eq!(error_at("torus { major 1 minor 2 }"), (1, 1));
+        assert_eq!(error_at("cuboid { min <1, 1, 1> }"), (1, 1));
+        assert_eq!(error_at("torus { radius 1 }"), (1, 9));
+    }
+}
@@ -0,0 +1,310 @@
+use crate::{Camera, Color, Hit, Light, Material, Ray, Renderer, Vector, BLACK, WHITE};
+
+// secondary rays start slightly off the surface to avoid self intersection
+const SHADOW_EPSILON: f64 = 1e-6;
+
+pub const DEFAULT_MAX_DEPTH: u32 = 5;
+
+// Blinn-Phong local illumination with hard shadows, plus recursive mirror
+// reflection and refraction weighted by Fresnel (Schlick approximation).
+// Lights are kept in camera cs, like rays.
+pub struct BlinnPhong {
+    lights: Vec<Light>,
+    materials: Vec<Material>,
+    pub ambient: Color,
+    pub background: Color,
+    pub shadows: bool,
+    pub max_depth: u32,
+}
+
+impl BlinnPhong {
+    // materials[i] is the material of shape i, lights are in reference cs
+    pub fn new(camera: &Camera, lights: &[Light], materials: Vec<Material>) -> BlinnPhong {
+        let m = camera.get_matrix_to_lcs();
+
+        BlinnPhong {
+            lights: lights.iter().map(|l| l.transform(m)).collect(),
+            materials,
+            ambient: WHITE,
+            background: BLACK,
+            shadows: true,
+            max_depth: DEFAULT_MAX_DEPTH,
+        }
+    }
+
+    pub fn shade(&self, renderer: &Renderer, ray: &Ray, hit: Option<(usize, &Hit)>) -> Color {
+        self.shade_depth(renderer, ray, hit, 0)
+    }
+
+    fn trace(&self, renderer: &Renderer, ray: &Ray, depth: u32) -> Color {
+        let hit = renderer.closest_hit(ray);
+        self.shade_depth(renderer, ray, hit.as_ref().map(|(i, h)| (*i, h)), depth)
+    }
+
+    fn shade_depth(
+        &self,
+        renderer: &Renderer,
+        ray: &Ray,
+        hit: Option<(usize, &Hit)>,
+        depth: u32,
+    ) -> Color {
+        let Some((i, hit)) = hit else {
+            return self.background;
+        };
+
+        let m = &self.materials[i];
+        let d = ray.v.clone().unit();
+        let mut c = (1. - m.transparency) * self.local(renderer, m, hit, &d);
+
+        if depth >= self.max_depth || (m.reflection <= 0. && m.transparency <= 0.) {
+            return c;
+        }
+
+        let mut kr = m.reflection;
+        if m.transparency > 0. {
+            // hit.normal faces the ray: front face means entering the material
+            let eta = if hit.front_face { 1. / m.ior } else { m.ior };
+            match refract(&d, &hit.normal, eta) {
+                Some(t) => {
+                    let cos = if eta > 1. {
+                        -(&t * &hit.normal)
+                    } else {
+                        -(&d * &hit.normal)
+                    };
+                    let fresnel = schlick(cos, eta);
+                    kr += m.transparency * fresnel;
+
+                    let origin = &hit.point - SHADOW_EPSILON * &hit.normal;
+                    let transmitted = self.trace(renderer, &Ray::new(origin, t), depth + 1);
+                    c += m.transparency * (1. - fresnel) * transmitted;
+                }
+                None => kr += m.transparency, // total internal reflection
+            }
+        }
+
+        if kr > 0. {
+            let origin = &hit.point + SHADOW_EPSILON * &hit.normal;
+            let r = reflect(&d, &hit.normal);
+            c += kr * self.trace(renderer, &Ray::new(origin, r), depth + 1);
+        }
+        c
+    }
+
+    // ambient, diffuse and specular terms, d being the unit ray direction
+    fn local(&self, renderer: &Renderer, m: &Material, hit: &Hit, d: &Vector) -> Color {
+        let v = -d;
+        let mut c = m.ambient * (m.color * self.ambient);
+
+        let origin = &hit.point + SHADOW_EPSILON * &hit.normal;
+
+        for light in &self.lights {
+            let Some((l, dist, li)) = light.illuminate(&hit.point) else {
+                continue;
+            };
+
+            let n_dot_l = &hit.normal * &l;
+            if n_dot_l <= 0. {
+                continue;
+            }
+
+            if self.shadows && renderer.occluded(&Ray::new(origin.clone(), l.clone()), 0., dist) {
+                continue;
+            }
+            c += m.diffuse * n_dot_l * (m.color * li);
+
+            let h = (&l + &v).unit();
+            let n_dot_h = (&hit.normal * &h).max(0.);
+            c += m.specular * n_dot_h.powf(m.shininess) * li;
+        }
+        c
+    }
+}
+
+// mirror direction of d about unit normal n
+fn reflect(d: &Vector, n: &Vector) -> Vector {
+    d - (2. * (d * n)) * n
+}
+
+// Snell refraction of unit d through unit normal n (facing d), eta being
+// n1 / n2. None on total internal reflection.
+fn refract(d: &Vector, n: &Vector, eta: f64) -> Option<Vector> {
+    let cos_i = -(d * n);
+    let sin2_t = eta * eta * (1. - cos_i * cos_i);
+    if sin2_t > 1. {
+        None
+    } else {
+        let cos_t = (1. - sin2_t).sqrt();
+        Some((eta * d + (eta * cos_i - cos_t) * n).unit())
+    }
+}
+
+// Schlick approximation of Fresnel reflectance, cos being the cosine of the
+// angle on the less dense side
+fn schlick(cos: f64, eta: f64) -> f64 {
+    let r0 = ((eta - 1.) / (eta + 1.)).powi(2);
+    r0 + (1. - r0) * (1. - cos).powi(5)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{nearly_equal, Ball, Cs, Point, Shapes};
+
+    fn empty_renderer() -> Renderer {
+        Renderer::new(Camera::new(), vec![]).unwrap()
+    }
+
+    fn hit_facing_camera() -> (Ray, Hit) {
+        let ray = Ray::new(Point::new(0., 0., 0.), Vector::new(0., 0., 1.));
+        let p = Point::new(0., 0., 10.);
+        let hit = Hit::new(10., p.clone(), p, Vector::new(0., 0., -1.), &ray.v);
+        (ray, hit)
+    }
+
+    #[test]
+    fn headlight() {
+        let light = Light::Point {
+            position: Point::new(0., 0., 0.),
+            color: WHITE,
+        };
+        let material = Material {
+            color: Color::new(1., 0.5, 0.),
+            ambient: 0.1,
+            diffuse: 0.5,
+            specular: 0.25,
+            shininess: 10.,
+            ..Material::default()
+        };
+        let shader = BlinnPhong::new(&Camera::new(), &[light], vec![material]);
+
+        let (ray, hit) = hit_facing_camera();
+        let c = shader.shade(&empty_renderer(), &ray, Some((0, &hit)));
+        assert!(c.nearly_equal(&Color::new(0.85, 0.55, 0.25)));
+    }
+
+    #[test]
+    fn light_behind() {
+        let light = Light::Directional {
+            direction: Vector::new(0., 0., -1.),
+            color: WHITE,
+        };
+        let shader = BlinnPhong::new(&Camera::new(), &[light], vec![Material::default()]);
+
+        let (ray, hit) = hit_facing_camera();
+        let c = shader.shade(&empty_renderer(), &ray, Some((0, &hit)));
+        assert!(c.nearly_equal(&Color::new(0.1, 0.1, 0.1)));
+        assert_eq!(shader.shade(&empty_renderer(), &ray, None), BLACK);
+    }
+
+    #[test]
+    fn shadow() {
+        // ball between the hit point and the light
+        let mut ball = Ball::build(1.);
+        let mut cs = Cs::new();
+        cs.translate(&Vector::new(0., 0., 5.));
+        ball.set_shape_cs(cs);
+        let shapes: Vec<Box<dyn Shapes>> = vec![Box::new(ball)];
+        let renderer = Renderer::new(Camera::new(), shapes).unwrap();
+
+        let light = Light::Point {
+            position: Point::new(0., 0., 0.),
+            color: WHITE,
+        };
+        let mut shader = BlinnPhong::new(&Camera::new(), &[light], vec![Material::default()]);
+
+        let (ray, hit) = hit_facing_camera();
+        let c = shader.shade(&renderer, &ray, Some((0, &hit)));
+        assert!(c.nearly_equal(&Color::new(0.1, 0.1, 0.1)));
+
+        shader.shadows = false;
+        let c = shader.shade(&renderer, &ray, Some((0, &hit)));
+        assert!(c.nearly_equal(&Color::new(1.3, 1.3, 1.3)));
+    }
+
+    #[test]
+    fn snell() {
+        let d = Vector::new(1., -1., 0.).unit();
+        let n = Vector::new(0., 1., 0.);
+
+        assert!(reflect(&d, &n).nearly_equal(&Vector::new(1., 1., 0.).unit()));
+        assert!(refract(&d, &n, 1.).unwrap().nearly_equal(&d));
+
+        // sin(t) = sin(45°) / 1.5
+        let t = refract(&d, &n, 1. / 1.5).unwrap();
+        assert!(nearly_equal(t.x, (0.5f64).sqrt() / 1.5));
+
+        // critical angle of glass is ~41.8°
+        assert!(refract(&d, &n, 1.5).is_none());
+    }
+
+    #[test]
+    fn fresnel() {
+        assert!(nearly_equal(schlick(1., 1. / 1.5), 0.04));
+        assert!(nearly_equal(schlick(0., 1. / 1.5), 1.));
+    }
+
+    fn ball(z: f64, radius: f64) -> Box<dyn Shapes> {
+        let mut ball = Ball::build(radius);
+        let mut cs = Cs::new();
+        cs.translate(&Vector::new(0., 0., z));
+        ball.set_shape_cs(cs);
+        Box::new(ball)
+    }
+
+    #[test]
+    fn mirror() {
+        // a perfect mirror ball seen head on reflects the camera side: black
+        // background, then a white ball behind the camera
+        let renderer = Renderer::new(Camera::new(), vec![ball(10., 1.), ball(-10., 1.)]).unwrap();
+        let mirror = Material {
+            color: BLACK,
+            ambient: 0.,
+            reflection: 1.,
+            ..Material::default()
+        };
+        let white = Material {
+            color: WHITE,
+            ambient: 1.,
+            diffuse: 0.,
+            specular: 0.,
+            ..Material::default()
+        };
+        let mut shader = BlinnPhong::new(&Camera::new(), &[], vec![mirror, white]);
+
+        let ray = Ray::new(Point::new(0., 0., 0.), Vector::new(0., 0., 1.));
+        let hit = renderer.closest_hit(&ray).unwrap();
+        let c = shader.shade(&renderer, &ray, Some((hit.0, &hit.1)));
+        assert!(c.nearly_equal(&WHITE));
+
+        shader.max_depth = 0;
+        let c = shader.shade(&renderer, &ray, Some((hit.0, &hit.1)));
+        assert!(c.nearly_equal(&BLACK));
+    }
+
+    #[test]
+    fn glass() {
+        // head on, a glass ball lets 0.96 * 0.96 of the light behind it through,
+        // plus a little coming from internal reflections
+        let renderer = Renderer::new(Camera::new(), vec![ball(10., 1.), ball(30., 5.)]).unwrap();
+        let glass = Material {
+            color: BLACK,
+            ambient: 0.,
+            transparency: 1.,
+            ior: 1.5,
+            ..Material::default()
+        };
+        let white = Material {
+            color: WHITE,
+            ambient: 1.,
+            diffuse: 0.,
+            specular: 0.,
+            ..Material::default()
+        };
+        let shader = BlinnPhong::new(&Camera::new(), &[], vec![glass, white]);
+
+        let ray = Ray::new(Point::new(0., 0., 0.), Vector::new(0., 0., 1.));
+        let hit = renderer.closest_hit(&ray).unwrap();
+        let c = shader.shade(&renderer, &ray, Some((hit.0, &hit.1)));
+        assert!(c.r > 0.96 * 0.96 && c.r < 0.96 * 0.96 + 0.002);
+    }
+}
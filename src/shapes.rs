@@ -1,9 +1,10 @@
-use super::{Cs, Ray};
-use crate::{Camera, Matrix};
+use super::{Aabb, Cs, Hit, IntervalList, Ray};
+use crate::{Camera, Matrix, Point, Vector};
 
-pub trait Shapes {
+pub trait Shapes: Send + Sync {
     fn get_matrix_to_lcs(&self) -> &Matrix;
     fn get_matrix_to_rcs(&self) -> &Matrix;
+    fn get_transform(&self) -> &Matrix;
     fn set_transform(&mut self, m: Matrix);
 
     fn set_shape_cs(&mut self, cs: Cs);
@@ -12,10 +13,80 @@ pub trait Shapes {
     }
     fn intersect(&self, ray: &Ray) -> bool;
     fn intersect_min(&self, ray: &Ray) -> Option<f64>;
+
+    // every entry/exit interval of the ray parameter, sorted and disjoint
+    fn intervals(&self, ray: &Ray) -> IntervalList;
+
+    // true if the surface is crossed for some t in [t_min, t_max]
+    fn any_hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> bool {
+        self.intervals(ray).iter().any(|i| {
+            [i.inf().unwrap(), i.sup().unwrap()]
+                .iter()
+                .any(|k| *k >= t_min && *k <= t_max)
+        })
+    }
+
+    // bounding box in shape cs
+    fn local_bounds(&self) -> Aabb;
+
+    // bounding box in reference cs
+    fn bounds(&self) -> Aabb {
+        self.local_bounds().transform(self.get_matrix_to_rcs())
+    }
+
+    // outward normal at point p (p and normal in shape cs)
+    fn normal_at(&self, p: &Point) -> Vector;
+
+    fn hit(&self, ray: &Ray) -> Option<Hit> {
+        self.intersect_min(ray).and_then(|t| self.hit_at(ray, t))
+    }
+
+    // hit record at t, a crossing given by intersect_min or intervals; None
+    // if the surface is not found there
+    fn hit_at(&self, ray: &Ray, t: f64) -> Option<Hit> {
+        let point = &ray.o + t * &ray.v;
+        let local = self.get_transform() * &point;
+        let world_point = self.get_matrix_to_rcs() * &local;
+
+        // normals are transformed back to camera cs with the inverse-transpose
+        // of the camera-to-shape matrix, i.e. its plain transpose
+        let normal = self.get_transform().transpose() * self.normal_at(&local);
+
+        Some(Hit::new(t, point, world_point, normal, &ray.v))
+    }
+}
+
+// below this sine, a ray is parallel to a direction or a plane: relative to
+// the lengths, shapes may be scaled to any size
+const PARALLEL_TH: f64 = 1e-12;
+
+// first finite bound ahead of the ray origin, for shapes whose
+// intersect_min comes from their intervals
+fn first_bound(intervals: &IntervalList) -> Option<f64> {
+    intervals
+        .iter()
+        .flat_map(|i| [i.inf().unwrap(), i.sup().unwrap()])
+        .find(|k| *k > 0. && k.is_finite())
 }
 
 mod ball;
+mod cone;
+mod csg;
+mod cuboid;
 mod cylinder;
+mod disk;
+mod mesh;
+mod plane;
+mod torus;
+mod triangle;
 
 pub use ball::Ball;
+pub use cone::Cone;
+pub use csg::{Csg, CsgOp};
+pub use cuboid::Cuboid;
 pub use cylinder::Cylinder;
+pub use disk::Disk;
+pub use mesh::Mesh;
+pub use plane::Plane;
+pub use torus::Torus;
+pub use triangle::Triangle;
@@ -1,4 +1,6 @@
-use crate::{nearly_equal, Cs, Matrix, Ray, Shapes};
+use crate::{
+    solve_quadratic, Aabb, Bound, Cs, Interval, IntervalList, Matrix, Point, Ray, Shapes, Vector,
+};
 
 pub struct Ball {
     pub cs: Cs,
@@ -17,6 +19,27 @@ impl Ball {
             cam_to_lcs: Matrix::default(),
         }
     }
+
+    // crossings of the line with the sphere
+    fn roots(&self, ray: &Ray) -> Vec<f64> {
+        let ray = Ray {
+            v: &self.cam_to_lcs * &ray.v,
+            o: &self.cam_to_lcs * &ray.o,
+        };
+
+        // local direction is not unit when the ball cs is scaled
+        let a = ray.v.square_length();
+
+        // solved from the point of closest approach to the center: b vanishes
+        // and c keeps its precision for distant or grazing rays
+        let tc = -(ray.v.x * ray.o.x + ray.v.y * ray.o.y + ray.v.z * ray.o.z) / a;
+        let p = &ray.o + tc * &ray.v;
+        let c = p.x * p.x + p.y * p.y + p.z * p.z - self.radius * self.radius;
+        solve_quadratic(a, 0., c)
+            .into_iter()
+            .map(|k| tc + k)
+            .collect()
+    }
 }
 
 impl Shapes for Ball {
@@ -24,6 +47,10 @@ impl Shapes for Ball {
         self.cam_to_lcs = m;
     }
 
+    fn get_transform(&self) -> &Matrix {
+        &self.cam_to_lcs
+    }
+
     fn get_matrix_to_lcs(&self) -> &Matrix {
         self.cs.get_matrix_to_lcs()
     }
@@ -37,39 +64,144 @@ impl Shapes for Ball {
     }
 
     fn intersect(&self, ray: &Ray) -> bool {
-        let ray = Ray {
-            v: &self.cam_to_lcs * &ray.v,
-            o: &self.cam_to_lcs * &ray.o,
-        };
-
-        let b = 2. * (ray.v.x * ray.o.x + ray.v.y * ray.o.y + ray.v.z * ray.o.z);
-        let c =
-            ray.o.x * ray.o.x + ray.o.y * ray.o.y + ray.o.z * ray.o.z - self.radius * self.radius;
-        let delta = b * b - 4. * c;
-
-        delta >= 0.
+        !self.roots(ray).is_empty()
     }
+
     fn intersect_min(&self, ray: &Ray) -> Option<f64> {
-        let ray = Ray {
-            v: &self.cam_to_lcs * &ray.v,
-            o: &self.cam_to_lcs * &ray.o,
-        };
-        assert!(nearly_equal(ray.v.length(), 1.));
-
-        let b = 2. * (ray.v.x * ray.o.x + ray.v.y * ray.o.y + ray.v.z * ray.o.z);
-        let c =
-            ray.o.x * ray.o.x + ray.o.y * ray.o.y + ray.o.z * ray.o.z - self.radius * self.radius;
-        let delta = b * b - 4. * c;
-
-        if delta >= 0. {
-            let k = (-b - delta.sqrt()) / 2.;
-            if k > 0. {
-                Some(k)
-            } else {
-                None
-            }
-        } else {
-            None
+        // far root is the exit point of a ray starting inside the ball
+        self.roots(ray).into_iter().find(|k| *k > 0.)
+    }
+
+    fn intervals(&self, ray: &Ray) -> IntervalList {
+        match self.roots(ray)[..] {
+            [k1, k2] => IntervalList::from(Interval::new(Bound::Closed(k1), Bound::Closed(k2))),
+            _ => IntervalList::new(),
         }
     }
+
+    fn local_bounds(&self) -> Aabb {
+        let r = self.radius;
+        Aabb::new(Point::new(-r, -r, -r), Point::new(r, r, r))
+    }
+
+    fn normal_at(&self, p: &Point) -> Vector {
+        Vector::new(p.x, p.y, p.z)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{nearly_equal, Camera};
+
+    #[test]
+    fn hit_1() {
+        let mut ball = Ball::build(2.);
+        let mut cs = Cs::new();
+        cs.translate(&Vector::new(0., 0., 10.));
+        ball.set_shape_cs(cs);
+        ball.compute_camcs_to_shapecs(&Camera::new());
+
+        let ray = Ray::new(Point::new(0., 0., 0.), Vector::new(0., 0., 1.));
+        let hit = ball.hit(&ray).unwrap();
+        assert!(nearly_equal(hit.t, 8.));
+        assert!(hit.point.nearly_equal(&Point::new(0., 0., 8.)));
+        assert!(hit.world_point.nearly_equal(&Point::new(0., 0., 8.)));
+        assert!(hit.normal.nearly_equal(&Vector::new(0., 0., -1.)));
+        assert!(hit.front_face);
+    }
+
+    #[test]
+    fn scaled() {
+        let mut ball = Ball::build(1.);
+        let mut cs = Cs::new();
+        cs.scale(2.);
+        cs.translate(&Vector::new(0., 0., 10.));
+        ball.set_shape_cs(cs);
+        ball.compute_camcs_to_shapecs(&Camera::new());
+
+        let ray = Ray::new(Point::new(0., 0., 0.), Vector::new(0., 0., 1.));
+        assert!(ball.intersect(&ray));
+        let hit = ball.hit(&ray).unwrap();
+        assert!(nearly_equal(hit.t, 8.));
+        assert!(hit.normal.nearly_equal(&Vector::new(0., 0., -1.)));
+
+        let ray = Ray::new(Point::new(1.9, 0., 0.), Vector::new(0., 0., 1.));
+        assert!(ball.intersect(&ray));
+        let ray = Ray::new(Point::new(2.1, 0., 0.), Vector::new(0., 0., 1.));
+        assert!(!ball.intersect(&ray));
+    }
+
+    #[test]
+    fn hit_inside() {
+        let mut ball = Ball::build(2.);
+        ball.compute_camcs_to_shapecs(&Camera::new());
+
+        let ray = Ray::new(Point::new(0., 0., 1.), Vector::new(0., 0., 1.));
+        let hit = ball.hit(&ray).unwrap();
+        assert!(nearly_equal(hit.t, 1.));
+        assert!(!hit.front_face);
+        assert!(hit.normal.nearly_equal(&Vector::new(0., 0., -1.)));
+    }
+
+    #[test]
+    fn ellipsoid() {
+        let mut ball = Ball::build(1.);
+        let mut cs = Cs::new();
+        cs.scale_xyz(1., 2., 3.);
+        cs.rotate_x(90.);
+        ball.set_shape_cs(cs);
+        ball.compute_camcs_to_shapecs(&Camera::new());
+
+        // semi axes are now 1 along x, 3 along y, 2 along z
+        let ray = Ray::new(Point::new(0., 0., -10.), Vector::new(0., 0., 1.));
+        let hit = ball.hit(&ray).unwrap();
+        assert!(nearly_equal(hit.t, 8.));
+        assert!(hit.normal.nearly_equal(&Vector::new(0., 0., -1.)));
+
+        let ray = Ray::new(Point::new(0., -10., 0.), Vector::new(0., 1., 0.));
+        assert!(nearly_equal(ball.intersect_min(&ray).unwrap(), 7.));
+
+        // normal at 45° on the x/y ellipse is not the radial direction
+        let ray = Ray::new(Point::new(0.5, -10., 0.), Vector::new(0., 1., 0.));
+        let hit = ball.hit(&ray).unwrap();
+        let expected = Vector::new(0.5, -(0.75f64.sqrt() / 3.), 0.).unit();
+        assert!(hit.normal.nearly_equal(&expected));
+    }
+
+    #[test]
+    fn distant() {
+        let mut ball = Ball::build(1.);
+        let mut cs = Cs::new();
+        cs.translate(&Vector::new(0., 0., 1e7));
+        ball.set_shape_cs(cs);
+        ball.compute_camcs_to_shapecs(&Camera::new());
+
+        // both crossings keep their precision far from the origin
+        let ray = Ray::new(Point::new(0.6, 0., 0.), Vector::new(0., 0., 1.));
+        let list = ball.intervals(&ray);
+        let i = &list.as_slice()[0];
+        assert!((i.inf().unwrap() - (1e7 - 0.8)).abs() < 1e-8);
+        assert!((i.sup().unwrap() - (1e7 + 0.8)).abs() < 1e-8);
+
+        // grazing rays
+        let ray = Ray::new(Point::new(1. - 1e-9, 0., 0.), Vector::new(0., 0., 1.));
+        let t = ball.intersect_min(&ray).unwrap();
+        assert!((t - (1e7 - (2e-9f64).sqrt())).abs() < 1e-8);
+        let ray = Ray::new(Point::new(1. + 1e-9, 0., 0.), Vector::new(0., 0., 1.));
+        assert!(!ball.intersect(&ray));
+    }
+
+    #[test]
+    fn bounds() {
+        let mut ball = Ball::build(2.);
+        let mut cs = Cs::new();
+        cs.scale_xyz(1., 2., 1.);
+        cs.translate(&Vector::new(0., 0., 10.));
+        ball.set_shape_cs(cs);
+
+        let b = ball.bounds();
+        assert!(b.min.nearly_equal(&Point::new(-2., -4., 8.)));
+        assert!(b.max.nearly_equal(&Point::new(2., 4., 12.)));
+    }
 }
@@ -0,0 +1,275 @@
+use super::{first_bound, Shapes, PARALLEL_TH};
+use crate::{
+    deg_to_rad, nearly_equal, solve_quadratic, Aabb, Bound, Cs, Interval, IntervalList, Matrix,
+    Point, Ray, Vector, J,
+};
+
+// Cone around the y axis with its apex at origin: x² + z² = (y tan(angle))².
+// Infinite double cone unless built with_height: then only the upper nappe
+// is kept, y in [0, height], closed by a cap at y = height if capped.
+pub struct Cone {
+    pub cs: Cs,
+    pub cam_to_lcs: Matrix,
+    pub angle: f64, // half-angle in degrees
+    pub slope2: f64,
+    pub height: f64,
+    pub caps: bool,
+}
+
+impl Cone {
+    pub fn build(angle: f64) -> Cone {
+        assert!(angle > 0. && angle < 90.);
+        let slope = deg_to_rad(angle).tan();
+
+        Cone {
+            cs: Cs::new(),
+            cam_to_lcs: Matrix::default(),
+            angle,
+            slope2: slope * slope,
+            height: f64::INFINITY,
+            caps: false,
+        }
+    }
+
+    pub fn with_height(mut self, height: f64, caps: bool) -> Cone {
+        assert!(height > 0.);
+        self.height = height;
+        self.caps = caps;
+        self
+    }
+
+    pub fn is_finite(&self) -> bool {
+        self.height.is_finite()
+    }
+
+    // where x² + z² - (y tan(angle))² <= 0 along the line, ray in shape cs
+    fn side_intervals(&self, ray: &Ray) -> IntervalList {
+        let k2 = self.slope2;
+        let (vr2, vy2) = (
+            ray.v.x * ray.v.x + ray.v.z * ray.v.z,
+            k2 * ray.v.y * ray.v.y,
+        );
+        let a = vr2 - vy2;
+        let (ox, oy, oz) = (ray.o.x * ray.v.x, k2 * ray.o.y * ray.v.y, ray.o.z * ray.v.z);
+        let b = 2. * (ox + oz - oy);
+        let c = ray.o.x * ray.o.x + ray.o.z * ray.o.z - k2 * ray.o.y * ray.o.y;
+        let all = || {
+            IntervalList::from(Interval::new(
+                Bound::NegativeInfinity,
+                Bound::PositiveInfinity,
+            ))
+        };
+
+        // a and b vanish by cancellation: compared to their terms
+        if a.abs() <= PARALLEL_TH * (vr2 + vy2) {
+            // parallel to a generatrix: a single crossing, or none
+            if b.abs() <= 2. * PARALLEL_TH * (ox.abs() + oy.abs() + oz.abs()) {
+                return if c <= 0. { all() } else { IntervalList::new() };
+            }
+            let k = -c / b;
+            return if b > 0. {
+                IntervalList::from(Interval::new(Bound::NegativeInfinity, Bound::Closed(k)))
+            } else {
+                IntervalList::from(Interval::new(Bound::Closed(k), Bound::PositiveInfinity))
+            };
+        }
+
+        let [k1, k2] = solve_quadratic(a, b, c)[..] else {
+            return if a > 0. { IntervalList::new() } else { all() };
+        };
+        if a > 0. {
+            IntervalList::from(Interval::new(Bound::Closed(k1), Bound::Closed(k2)))
+        } else {
+            // the line goes through both nappes
+            IntervalList::from(vec![
+                Interval::new(Bound::NegativeInfinity, Bound::Closed(k1)),
+                Interval::new(Bound::Closed(k2), Bound::PositiveInfinity),
+            ])
+        }
+    }
+
+    // ray parameters with y in [0, height], ray in shape cs
+    fn slab(&self, ray: &Ray) -> IntervalList {
+        if ray.v.y.abs() <= PARALLEL_TH * ray.v.length() {
+            if ray.o.y >= 0. && ray.o.y <= self.height {
+                IntervalList::from(Interval::new(
+                    Bound::NegativeInfinity,
+                    Bound::PositiveInfinity,
+                ))
+            } else {
+                IntervalList::new()
+            }
+        } else {
+            let k1 = -ray.o.y / ray.v.y;
+            let k2 = (self.height - ray.o.y) / ray.v.y;
+            IntervalList::from(Interval::new(
+                Bound::Closed(k1.min(k2)),
+                Bound::Closed(k1.max(k2)),
+            ))
+        }
+    }
+}
+
+impl Shapes for Cone {
+    fn set_transform(&mut self, m: Matrix) {
+        self.cam_to_lcs = m;
+    }
+
+    fn get_transform(&self) -> &Matrix {
+        &self.cam_to_lcs
+    }
+
+    fn get_matrix_to_lcs(&self) -> &Matrix {
+        self.cs.get_matrix_to_lcs()
+    }
+
+    fn get_matrix_to_rcs(&self) -> &Matrix {
+        self.cs.get_matrix_to_rcs()
+    }
+
+    fn set_shape_cs(&mut self, cs: Cs) {
+        self.cs = cs;
+    }
+
+    fn intersect(&self, ray: &Ray) -> bool {
+        self.intersect_min(ray).is_some()
+    }
+
+    fn intersect_min(&self, ray: &Ray) -> Option<f64> {
+        first_bound(&self.intervals(ray))
+    }
+
+    fn intervals(&self, ray: &Ray) -> IntervalList {
+        let ray = Ray {
+            v: &self.cam_to_lcs * &ray.v,
+            o: &self.cam_to_lcs * &ray.o,
+        };
+
+        let side = self.side_intervals(&ray);
+        if !self.is_finite() {
+            side
+        } else if self.caps {
+            side.intersection(&self.slab(&ray))
+        } else {
+            // open cone: side crossings of the upper nappe
+            let crossings: Vec<Interval> = side
+                .iter()
+                .flat_map(|i| [i.inf().unwrap(), i.sup().unwrap()])
+                .filter(|k| {
+                    let y = ray.o.y + k * ray.v.y;
+                    k.is_finite() && y >= 0. && y <= self.height
+                })
+                .map(Interval::singleton)
+                .collect();
+            IntervalList::from(crossings)
+        }
+    }
+
+    // infinite unless finite
+    fn local_bounds(&self) -> Aabb {
+        if self.is_finite() {
+            let r = self.height * self.slope2.sqrt();
+            Aabb::new(Point::new(-r, 0., -r), Point::new(r, self.height, r))
+        } else {
+            Aabb::infinite()
+        }
+    }
+
+    fn normal_at(&self, p: &Point) -> Vector {
+        if self.caps && nearly_equal(p.y, self.height) {
+            J
+        } else {
+            let n = Vector::new(p.x, -self.slope2 * p.y, p.z);
+            // no normal at the apex, take the axis
+            if n.nearly_zero() {
+                -J
+            } else {
+                n
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Camera, K};
+
+    #[test]
+    fn infinite() {
+        let mut cone = Cone::build(45.);
+        cone.compute_camcs_to_shapecs(&Camera::new());
+
+        // horizontal ray at y = 1 crosses the upper nappe at x = ±1
+        let ray = Ray::new(Point::new(-5., 1., 0.), Vector::new(1., 0., 0.));
+        let hit = cone.hit(&ray).unwrap();
+        assert!(nearly_equal(hit.t, 4.));
+        assert!(hit.normal.nearly_equal(&Vector::new(-1., -1., 0.).unit()));
+
+        // along the axis, inside both nappes all the way
+        let ray = Ray::new(Point::new(0., -5., 0.), J);
+        let list = cone.intervals(&ray);
+        assert!(list.contains(0.) && list.contains(10.));
+
+        // vertical line off axis leaves through the lower nappe, enters the upper one
+        let ray = Ray::new(Point::new(1., -5., 0.), J);
+        let list = cone.intervals(&ray);
+        assert_eq!(list.len(), 2);
+        assert!(nearly_equal(list.as_slice()[0].sup().unwrap(), 4.));
+        assert!(nearly_equal(list.as_slice()[1].inf().unwrap(), 6.));
+    }
+
+    #[test]
+    fn large() {
+        let mut cone = Cone::build(45.);
+        let mut cs = Cs::new();
+        cs.scale(1e4);
+        cone.set_shape_cs(cs);
+        cone.compute_camcs_to_shapecs(&Camera::new());
+
+        // horizontal ray at y = 1e4 crosses the upper nappe at x = ±1e4
+        let ray = Ray::new(Point::new(-5e4, 1e4, 0.), Vector::new(1., 0., 0.));
+        let list = cone.intervals(&ray);
+        assert_eq!(list.len(), 1);
+        let i = &list.as_slice()[0];
+        assert!(nearly_equal(i.inf().unwrap(), 4e4));
+        assert!(nearly_equal(i.sup().unwrap(), 6e4));
+
+        // parallel to a generatrix, through the axis at y = 1e4
+        let ray = Ray::new(Point::new(-1e4, 0., 0.), Vector::new(1., 1., 0.));
+        let list = cone.intervals(&ray);
+        assert_eq!(list.len(), 1);
+        assert!(nearly_equal(list.as_slice()[0].inf().unwrap(), 5e3));
+    }
+
+    #[test]
+    fn capped() {
+        let mut cone = Cone::build(45.).with_height(2., true);
+        let mut cs = Cs::new();
+        cs.translate(&Vector::new(0., 0., 10.));
+        cone.set_shape_cs(cs);
+        cone.compute_camcs_to_shapecs(&Camera::new());
+
+        // y = 1: radius 1 circle
+        let ray = Ray::new(Point::new(0., 1., 0.), K);
+        let hit = cone.hit(&ray).unwrap();
+        assert!(nearly_equal(hit.t, 9.));
+
+        // from above, through the cap
+        let ray = Ray::new(Point::new(0.5, 5., 10.), Vector::new(0., -1., 0.));
+        let hit = cone.hit(&ray).unwrap();
+        assert!(nearly_equal(hit.t, 3.));
+        assert!(hit.normal.nearly_equal(&J));
+        let list = cone.intervals(&ray);
+        assert_eq!(list.len(), 1);
+        assert!(nearly_equal(list.as_slice()[0].sup().unwrap(), 4.5));
+
+        // lower nappe is gone
+        let ray = Ray::new(Point::new(0., -1., 0.), K);
+        assert!(!cone.intersect(&ray));
+
+        let b = cone.bounds();
+        assert!(b.min.nearly_equal(&Point::new(-2., 0., 8.)));
+        assert!(b.max.nearly_equal(&Point::new(2., 2., 12.)));
+    }
+}
@@ -0,0 +1,239 @@
+use super::{first_bound, Shapes};
+use crate::{Aabb, Cs, Hit, IntervalList, Matrix, Point, Ray, Vector};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CsgOp {
+    Union,
+    Intersection,
+    Difference,
+}
+
+impl CsgOp {
+    fn apply(&self, a: &IntervalList, b: &IntervalList) -> IntervalList {
+        let result = match self {
+            CsgOp::Union => a.union(b),
+            CsgOp::Intersection => a.intersection(b),
+            CsgOp::Difference => a.difference(b),
+        };
+
+        // solids meeting on a bound leave a single point of surface: drop it,
+        // unless an operand is a point there already (surface only shapes)
+        let is_point = |l: &IntervalList, k| l.iter().any(|i| i.get_singleton() == Some(k));
+        let mut intervals = result.into_vec();
+        intervals.retain(|i| {
+            i.get_singleton()
+                .is_none_or(|k| is_point(a, k) || is_point(b, k))
+        });
+        IntervalList::from(intervals)
+    }
+}
+
+// CSG node: children are placed in the node cs, the node in its parent cs
+pub struct Csg {
+    pub cs: Cs,
+    pub cam_to_lcs: Matrix,
+    pub op: CsgOp,
+    pub a: Box<dyn Shapes>,
+    pub b: Box<dyn Shapes>,
+}
+
+impl Csg {
+    pub fn build(op: CsgOp, a: Box<dyn Shapes>, b: Box<dyn Shapes>) -> Csg {
+        Csg {
+            cs: Cs::new(),
+            cam_to_lcs: Matrix::default(),
+            op,
+            a,
+            b,
+        }
+    }
+
+    pub fn union(a: Box<dyn Shapes>, b: Box<dyn Shapes>) -> Csg {
+        Csg::build(CsgOp::Union, a, b)
+    }
+
+    pub fn intersection(a: Box<dyn Shapes>, b: Box<dyn Shapes>) -> Csg {
+        Csg::build(CsgOp::Intersection, a, b)
+    }
+
+    pub fn difference(a: Box<dyn Shapes>, b: Box<dyn Shapes>) -> Csg {
+        Csg::build(CsgOp::Difference, a, b)
+    }
+
+    fn first_bound(&self, ray: &Ray) -> Option<f64> {
+        first_bound(&self.intervals(ray))
+    }
+}
+
+fn is_bound_of(intervals: &IntervalList, k: f64) -> bool {
+    intervals
+        .iter()
+        .any(|i| i.inf().unwrap() == k || i.sup().unwrap() == k)
+}
+
+impl Shapes for Csg {
+    fn set_transform(&mut self, m: Matrix) {
+        self.a.set_transform(self.a.get_matrix_to_lcs() * &m);
+        self.b.set_transform(self.b.get_matrix_to_lcs() * &m);
+        self.cam_to_lcs = m;
+    }
+
+    fn get_transform(&self) -> &Matrix {
+        &self.cam_to_lcs
+    }
+
+    fn get_matrix_to_lcs(&self) -> &Matrix {
+        self.cs.get_matrix_to_lcs()
+    }
+
+    fn get_matrix_to_rcs(&self) -> &Matrix {
+        self.cs.get_matrix_to_rcs()
+    }
+
+    fn set_shape_cs(&mut self, cs: Cs) {
+        self.cs = cs;
+    }
+
+    fn intersect(&self, ray: &Ray) -> bool {
+        self.first_bound(ray).is_some()
+    }
+
+    fn intersect_min(&self, ray: &Ray) -> Option<f64> {
+        self.first_bound(ray)
+    }
+
+    fn intervals(&self, ray: &Ray) -> IntervalList {
+        self.op
+            .apply(&self.a.intervals(ray), &self.b.intervals(ray))
+    }
+
+    // children bounds are in node cs
+    fn local_bounds(&self) -> Aabb {
+        match self.op {
+            CsgOp::Union => self.a.bounds().union(&self.b.bounds()),
+            CsgOp::Intersection => self.a.bounds().intersection(&self.b.bounds()),
+            CsgOp::Difference => self.a.bounds(),
+        }
+    }
+
+    fn normal_at(&self, _p: &Point) -> Vector {
+        unreachable!("CSG normals are provided by children through hit_at")
+    }
+
+    fn hit_at(&self, ray: &Ray, t: f64) -> Option<Hit> {
+        // bounds are copied from children intervals: exact match is safe
+        let (mut hit, from_b) = if is_bound_of(&self.a.intervals(ray), t) {
+            (self.a.hit_at(ray, t)?, false)
+        } else {
+            (self.b.hit_at(ray, t)?, true)
+        };
+
+        // child world point is expressed in node cs
+        hit.world_point = self.get_matrix_to_rcs() * &hit.world_point;
+        if from_b && self.op == CsgOp::Difference {
+            hit.front_face = !hit.front_face;
+        }
+        Some(hit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{nearly_equal, Ball, Camera, Cylinder};
+
+    fn ball_at(z: f64, radius: f64) -> Box<dyn Shapes> {
+        let mut ball = Ball::build(radius);
+        let mut cs = Cs::new();
+        cs.translate(&Vector::new(0., 0., z));
+        ball.set_shape_cs(cs);
+        Box::new(ball)
+    }
+
+    fn ray_k() -> Ray {
+        Ray::new(Point::new(0., 0., 0.), Vector::new(0., 0., 1.))
+    }
+
+    fn prepare(mut csg: Csg) -> Csg {
+        csg.compute_camcs_to_shapecs(&Camera::new());
+        csg
+    }
+
+    #[test]
+    fn union_1() {
+        let csg = prepare(Csg::union(ball_at(10., 2.), ball_at(13., 2.)));
+        let intervals = csg.intervals(&ray_k());
+
+        assert_eq!(intervals.len(), 1);
+        assert!(nearly_equal(intervals.as_slice()[0].inf().unwrap(), 8.));
+        assert!(nearly_equal(intervals.as_slice()[0].sup().unwrap(), 15.));
+    }
+
+    #[test]
+    fn intersection_1() {
+        // lens shape
+        let csg = prepare(Csg::intersection(ball_at(10., 2.), ball_at(13., 2.)));
+        let hit = csg.hit(&ray_k()).unwrap();
+
+        assert!(nearly_equal(hit.t, 11.));
+        assert!(hit.front_face);
+        assert!(hit.normal.nearly_equal(&Vector::new(0., 0., -1.)));
+    }
+
+    #[test]
+    fn difference_1() {
+        let csg = prepare(Csg::difference(ball_at(10., 2.), ball_at(8., 1.)));
+        let hit = csg.hit(&ray_k()).unwrap();
+
+        // entering through the carved out ball surface
+        assert!(nearly_equal(hit.t, 9.));
+        assert!(hit.front_face);
+        assert!(hit.normal.nearly_equal(&Vector::new(0., 0., -1.)));
+    }
+
+    #[test]
+    fn difference_2() {
+        // drilled ball
+        let mut cs = Cs::new();
+        cs.translate(&Vector::new(0., 0., 10.));
+        let mut csg = Csg::difference(Box::new(Ball::build(2.)), Box::new(Cylinder::build(0.5)));
+        csg.set_shape_cs(cs);
+        let csg = prepare(csg);
+
+        let ray = Ray::new(Point::new(0., 0., 0.), Vector::new(0., 0., 1.));
+        assert!(nearly_equal(csg.intersect_min(&ray).unwrap(), 8.));
+
+        let ray = Ray::new(Point::new(0., 3., 10.), Vector::new(0., -1., 0.));
+        assert!(!csg.intersect(&ray));
+    }
+
+    #[test]
+    fn difference_coincident() {
+        // the inner difference is the same ball, opened where the small ball
+        // touches it at t = 8: nothing is left
+        let inner = Csg::difference(ball_at(10., 2.), ball_at(7., 1.));
+        let csg = prepare(Csg::difference(ball_at(10., 2.), Box::new(inner)));
+
+        assert!(csg.intervals(&ray_k()).is_empty());
+        assert!(csg.hit(&ray_k()).is_none());
+    }
+
+    #[test]
+    fn bounds_1() {
+        // lens in the x/y plane, moved along z
+        let mut cs = Cs::new();
+        cs.translate(&Vector::new(0., 0., 5.));
+        let mut csg = Csg::intersection(ball_at(-1., 2.), ball_at(1., 2.));
+        csg.set_shape_cs(cs);
+
+        let b = csg.bounds();
+        assert!(b.min.nearly_equal(&Point::new(-2., -2., 4.)));
+        assert!(b.max.nearly_equal(&Point::new(2., 2., 6.)));
+
+        // drilled ball stays bounded
+        let csg = Csg::difference(ball_at(0., 2.), Box::new(Cylinder::build(0.5)));
+        assert!(csg.bounds().is_finite());
+        let csg = Csg::union(ball_at(0., 2.), Box::new(Cylinder::build(0.5)));
+        assert!(!csg.bounds().is_finite());
+    }
+}
@@ -0,0 +1,140 @@
+use super::{first_bound, Shapes};
+use crate::{Aabb, Bound, Cs, Interval, IntervalList, Matrix, Point, Ray, Vector};
+
+// Axis-aligned box of its cs, intersected with the slab method
+pub struct Cuboid {
+    pub cs: Cs,
+    pub cam_to_lcs: Matrix,
+    pub min: Point,
+    pub max: Point,
+}
+
+impl Cuboid {
+    pub fn build(min: Point, max: Point) -> Cuboid {
+        assert!(min.x < max.x && min.y < max.y && min.z < max.z);
+
+        Cuboid {
+            cs: Cs::new(),
+            cam_to_lcs: Matrix::default(),
+            min,
+            max,
+        }
+    }
+}
+
+impl Shapes for Cuboid {
+    fn set_transform(&mut self, m: Matrix) {
+        self.cam_to_lcs = m;
+    }
+
+    fn get_transform(&self) -> &Matrix {
+        &self.cam_to_lcs
+    }
+
+    fn get_matrix_to_lcs(&self) -> &Matrix {
+        self.cs.get_matrix_to_lcs()
+    }
+
+    fn get_matrix_to_rcs(&self) -> &Matrix {
+        self.cs.get_matrix_to_rcs()
+    }
+
+    fn set_shape_cs(&mut self, cs: Cs) {
+        self.cs = cs;
+    }
+
+    fn intersect(&self, ray: &Ray) -> bool {
+        self.intersect_min(ray).is_some()
+    }
+
+    fn intersect_min(&self, ray: &Ray) -> Option<f64> {
+        first_bound(&self.intervals(ray))
+    }
+
+    fn intervals(&self, ray: &Ray) -> IntervalList {
+        let ray = Ray {
+            v: &self.cam_to_lcs * &ray.v,
+            o: &self.cam_to_lcs * &ray.o,
+        };
+
+        match self
+            .local_bounds()
+            .intersect(&ray, f64::NEG_INFINITY, f64::INFINITY)
+        {
+            Some((k1, k2)) if k1.is_finite() && k2.is_finite() => {
+                IntervalList::from(Interval::new(Bound::Closed(k1), Bound::Closed(k2)))
+            }
+            // null direction
+            Some(_) => IntervalList::from(Interval::new(
+                Bound::NegativeInfinity,
+                Bound::PositiveInfinity,
+            )),
+            None => IntervalList::new(),
+        }
+    }
+
+    fn local_bounds(&self) -> Aabb {
+        Aabb::new(self.min.clone(), self.max.clone())
+    }
+
+    // normal of the face p is closest to, relative to the box size
+    fn normal_at(&self, p: &Point) -> Vector {
+        let offset = |k: f64, lo: f64, hi: f64| (2. * k - lo - hi) / (hi - lo);
+        let d = [
+            offset(p.x, self.min.x, self.max.x),
+            offset(p.y, self.min.y, self.max.y),
+            offset(p.z, self.min.z, self.max.z),
+        ];
+
+        let axis = (0..3)
+            .max_by(|a, b| d[*a].abs().total_cmp(&d[*b].abs()))
+            .unwrap();
+        let s = d[axis].signum();
+        match axis {
+            0 => Vector::new(s, 0., 0.),
+            1 => Vector::new(0., s, 0.),
+            _ => Vector::new(0., 0., s),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{nearly_equal, Camera, I, J, K};
+
+    #[test]
+    fn hit_1() {
+        let mut cuboid = Cuboid::build(Point::new(-1., -2., -3.), Point::new(1., 2., 3.));
+        let mut cs = Cs::new();
+        cs.translate(&Vector::new(0., 0., 10.));
+        cuboid.set_shape_cs(cs);
+        cuboid.compute_camcs_to_shapecs(&Camera::new());
+
+        let ray = Ray::new(Point::new(0.5, 1.5, 0.), K);
+        let hit = cuboid.hit(&ray).unwrap();
+        assert!(nearly_equal(hit.t, 7.));
+        assert!(hit.normal.nearly_equal(&-K));
+        let list = cuboid.intervals(&ray);
+        assert!(nearly_equal(list.as_slice()[0].sup().unwrap(), 13.));
+
+        // from inside, leaving through +x
+        let ray = Ray::new(Point::new(0., 0., 10.), I);
+        let hit = cuboid.hit(&ray).unwrap();
+        assert!(nearly_equal(hit.t, 1.));
+        assert!(!hit.front_face);
+
+        let ray = Ray::new(Point::new(0., 0., 0.), J);
+        assert!(!cuboid.intersect(&ray));
+    }
+
+    #[test]
+    fn normals() {
+        let cuboid = Cuboid::build(Point::new(-1., -2., -3.), Point::new(1., 2., 3.));
+        assert!(cuboid.normal_at(&Point::new(0.9, 2., 0.)).nearly_equal(&J));
+        assert!(cuboid
+            .normal_at(&Point::new(-1., 1.5, 2.5))
+            .nearly_equal(&-I));
+        assert!(cuboid.normal_at(&Point::new(0.5, 1., 3.)).nearly_equal(&K));
+    }
+}
@@ -1,11 +1,19 @@
-use super::Shapes;
-use crate::{Cs, Matrix, Ray, J};
+use super::{first_bound, Shapes, PARALLEL_TH};
+use crate::{
+    nearly_equal, solve_quadratic, Aabb, Bound, Cs, Interval, IntervalList, Matrix, Point, Ray,
+    Vector, J,
+};
 
+// Cylinder around the y axis, infinite unless built with_height: then it
+// spans y in [-height/2, height/2] and is a closed solid if capped, an open
+// tube otherwise.
 pub struct Cylinder {
     pub cs: Cs,
     pub cam_to_lcs: Matrix,
     pub radius: f64,
     pub radius2: f64,
+    pub height: f64,
+    pub caps: bool,
 }
 
 impl Cylinder {
@@ -18,9 +26,72 @@ impl Cylinder {
             cs,
             radius,
             radius2: radius * radius,
+            height: f64::INFINITY,
+            caps: false,
             cam_to_lcs: Matrix::default(),
         }
     }
+
+    pub fn with_height(mut self, height: f64, caps: bool) -> Cylinder {
+        assert!(height > 0.);
+        self.height = height;
+        self.caps = caps;
+        self
+    }
+
+    pub fn is_finite(&self) -> bool {
+        self.height.is_finite()
+    }
+
+    // intervals of the infinite cylinder, ray in shape cs
+    fn side_intervals(&self, ray: &Ray) -> IntervalList {
+        let val = ray.o.x * ray.o.x + ray.o.z * ray.o.z;
+        let a = ray.v.x * ray.v.x + ray.v.z * ray.v.z;
+        if a <= PARALLEL_TH * PARALLEL_TH * ray.v.square_length() {
+            // ray parallel to the axis: inside all along, or never
+            if val <= self.radius2 {
+                IntervalList::from(Interval::new(
+                    Bound::NegativeInfinity,
+                    Bound::PositiveInfinity,
+                ))
+            } else {
+                IntervalList::new()
+            }
+        } else {
+            // solved from the point of closest approach to the axis, as Ball
+            let tc = -(ray.v.x * ray.o.x + ray.v.z * ray.o.z) / a;
+            let (x, z) = (ray.o.x + tc * ray.v.x, ray.o.z + tc * ray.v.z);
+            match solve_quadratic(a, 0., x * x + z * z - self.radius2)[..] {
+                [k1, k2] => IntervalList::from(Interval::new(
+                    Bound::Closed(tc + k1),
+                    Bound::Closed(tc + k2),
+                )),
+                _ => IntervalList::new(),
+            }
+        }
+    }
+
+    // ray parameters between the cap planes, ray in shape cs
+    fn slab(&self, ray: &Ray) -> IntervalList {
+        let h = self.height / 2.;
+        if ray.v.y.abs() <= PARALLEL_TH * ray.v.length() {
+            if ray.o.y.abs() <= h {
+                IntervalList::from(Interval::new(
+                    Bound::NegativeInfinity,
+                    Bound::PositiveInfinity,
+                ))
+            } else {
+                IntervalList::new()
+            }
+        } else {
+            let k1 = (-h - ray.o.y) / ray.v.y;
+            let k2 = (h - ray.o.y) / ray.v.y;
+            IntervalList::from(Interval::new(
+                Bound::Closed(k1.min(k2)),
+                Bound::Closed(k1.max(k2)),
+            ))
+        }
+    }
 }
 
 impl Shapes for Cylinder {
@@ -28,6 +99,10 @@ impl Shapes for Cylinder {
         self.cam_to_lcs = m;
     }
 
+    fn get_transform(&self) -> &Matrix {
+        &self.cam_to_lcs
+    }
+
     fn get_matrix_to_lcs(&self) -> &Matrix {
         self.cs.get_matrix_to_lcs()
     }
@@ -40,36 +115,167 @@ impl Shapes for Cylinder {
         self.cs = cs;
     }
 
-    fn intersect(&self, _ray: &crate::Ray) -> bool {
-        false
+    fn intersect(&self, ray: &crate::Ray) -> bool {
+        !self.intervals(ray).is_empty()
     }
 
     fn intersect_min(&self, ray: &crate::Ray) -> Option<f64> {
+        if self.is_finite() {
+            return first_bound(&self.intervals(ray));
+        }
+
+        let local = Ray {
+            v: &self.cam_to_lcs * &ray.v,
+            o: &self.cam_to_lcs * &ray.o,
+        };
+
+        let val = local.o.x * local.o.x + local.o.z * local.o.z;
+        let a = local.v.x * local.v.x + local.v.z * local.v.z;
+        if a <= PARALLEL_TH * PARALLEL_TH * local.v.square_length() && val <= self.radius2 {
+            // ray along the axis (sine to J below PARALLEL_TH) inside the side:
+            // it never crosses the infinite cylinder but starts in it
+            Some(f64::MIN_POSITIVE)
+        } else {
+            first_bound(&self.side_intervals(&local))
+        }
+    }
+
+    fn intervals(&self, ray: &Ray) -> IntervalList {
         let ray = Ray {
             v: &self.cam_to_lcs * &ray.v,
             o: &self.cam_to_lcs * &ray.o,
         };
 
-        let val = ray.o.x * ray.o.x + ray.o.z * ray.o.z;
-        if (&ray.v ^ &J).nearly_zero() && val <= self.radius2 {
-            // FIXME: ray.v ^ J => ray.v.y ~ 0
-            Some(std::f64::MIN_POSITIVE)
+        let side = self.side_intervals(&ray);
+        if !self.is_finite() {
+            side
+        } else if self.caps {
+            side.intersection(&self.slab(&ray))
         } else {
-            let a = ray.v.x * ray.v.x + ray.v.z * ray.v.z;
-            let b = 2. * (ray.v.x * ray.o.x + ray.v.z * ray.o.z);
-            let c = val - self.radius2;
-
-            let delta = b * b - 4. * a * c;
-            if delta > 0. {
-                let k = (-b - delta.sqrt()) / (2. * a);
-                if k > 0. {
-                    Some(k)
-                } else {
-                    None
-                }
-            } else {
-                None
-            }
+            // open tube: side crossings between the cap planes
+            let h = self.height / 2.;
+            let crossings: Vec<Interval> = side
+                .iter()
+                .flat_map(|i| [i.inf().unwrap(), i.sup().unwrap()])
+                .filter(|k| k.is_finite() && (ray.o.y + k * ray.v.y).abs() <= h)
+                .map(Interval::singleton)
+                .collect();
+            IntervalList::from(crossings)
         }
     }
+
+    // infinite along y unless finite
+    fn local_bounds(&self) -> Aabb {
+        let r = self.radius;
+        let h = self.height / 2.;
+        Aabb::new(Point::new(-r, -h, -r), Point::new(r, h, r))
+    }
+
+    fn normal_at(&self, p: &Point) -> Vector {
+        let h = self.height / 2.;
+        if self.caps && nearly_equal(p.y, h) {
+            J
+        } else if self.caps && nearly_equal(p.y, -h) {
+            -J
+        } else {
+            Vector::new(p.x, 0., p.z)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Camera, K};
+
+    #[test]
+    fn intersect_1() {
+        let mut cyl = Cylinder::build(1.);
+        let mut cs = Cs::new();
+        cs.translate(&Vector::new(0., 0., 10.));
+        cyl.set_shape_cs(cs);
+        cyl.compute_camcs_to_shapecs(&Camera::new());
+
+        let ray = Ray::new(Point::new(0., 0., 0.), K);
+        assert!(cyl.intersect(&ray));
+        assert!(cyl.any_hit(&ray, 0., 9.5));
+        assert!(!cyl.any_hit(&ray, 0., 8.5));
+
+        let ray = Ray::new(Point::new(2., 0., 0.), K);
+        assert!(!cyl.intersect(&ray));
+    }
+
+    #[test]
+    fn large() {
+        // capped, radius and height 1e4 in camera cs
+        let mut cyl = Cylinder::build(1.).with_height(2., true);
+        let mut cs = Cs::new();
+        cs.scale(1e4);
+        cs.translate(&Vector::new(0., 0., 5e4));
+        cyl.set_shape_cs(cs);
+        cyl.compute_camcs_to_shapecs(&Camera::new());
+
+        let ray = Ray::new(Point::new(0., 0., 0.), Vector::new(0., 0.1, 1.).unit());
+        let list = cyl.intervals(&ray);
+        assert_eq!(list.len(), 1);
+        let i = &list.as_slice()[0];
+        assert!(nearly_equal(i.inf().unwrap() * ray.v.z, 4e4));
+        assert!(nearly_equal(i.sup().unwrap() * ray.v.z, 6e4));
+
+        // out through the top cap
+        let ray = Ray::new(Point::new(0., 0., 0.), Vector::new(0., 0.2, 1.).unit());
+        let list = cyl.intervals(&ray);
+        assert!(nearly_equal(
+            list.as_slice()[0].sup().unwrap() * ray.v.y,
+            1e4
+        ));
+    }
+
+    #[test]
+    fn capped() {
+        let mut cyl = Cylinder::build(1.).with_height(2., true);
+        let mut cs = Cs::new();
+        cs.translate(&Vector::new(0., 0., 10.));
+        cyl.set_shape_cs(cs);
+        cyl.compute_camcs_to_shapecs(&Camera::new());
+
+        // side, then through the top cap from above
+        let ray = Ray::new(Point::new(0., 0., 0.), K);
+        let hit = cyl.hit(&ray).unwrap();
+        assert!(nearly_equal(hit.t, 9.));
+        assert!(hit.normal.nearly_equal(&-K));
+
+        let ray = Ray::new(Point::new(0.5, 5., 10.), Vector::new(0., -1., 0.));
+        let hit = cyl.hit(&ray).unwrap();
+        assert!(nearly_equal(hit.t, 4.));
+        assert!(hit.normal.nearly_equal(&J));
+        let list = cyl.intervals(&ray);
+        assert_eq!(list.len(), 1);
+        assert!(nearly_equal(list.as_slice()[0].sup().unwrap(), 6.));
+
+        // above the top cap
+        let ray = Ray::new(Point::new(0., 1.5, 0.), K);
+        assert!(!cyl.intersect(&ray));
+
+        let b = cyl.bounds();
+        assert!(b.is_finite());
+        assert!(b.min.nearly_equal(&Point::new(-1., -1., 9.)));
+    }
+
+    #[test]
+    fn open() {
+        let mut cyl = Cylinder::build(1.).with_height(2., false);
+        cyl.compute_camcs_to_shapecs(&Camera::new());
+
+        // down the axis through both open ends
+        let ray = Ray::new(Point::new(0., 5., 0.), Vector::new(0., -1., 0.));
+        assert!(!cyl.intersect(&ray));
+
+        // oblique ray entering through the top opening hits the inner side
+        let ray = Ray::new(Point::new(0., 2., 0.), Vector::new(1., -1., 0.));
+        let hit = cyl.hit(&ray).unwrap();
+        assert!(nearly_equal(hit.t, 1.));
+        assert!(!hit.front_face);
+        assert_eq!(cyl.intervals(&ray).len(), 1);
+    }
 }
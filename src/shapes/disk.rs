@@ -0,0 +1,118 @@
+use super::Shapes;
+use crate::{nearly_zero, Aabb, Cs, Interval, IntervalList, Matrix, Point, Ray, Vector, J};
+
+// Disk centered on origin in the plane y = 0 of its cs, normal +y
+pub struct Disk {
+    pub cs: Cs,
+    pub cam_to_lcs: Matrix,
+    pub radius: f64,
+    pub radius2: f64,
+}
+
+impl Disk {
+    pub fn build(radius: f64) -> Disk {
+        assert!(radius > 0.);
+
+        Disk {
+            cs: Cs::new(),
+            cam_to_lcs: Matrix::default(),
+            radius,
+            radius2: radius * radius,
+        }
+    }
+
+    // crossing of the line with the disk, for any t
+    fn crossing(&self, ray: &Ray) -> Option<f64> {
+        let ray = Ray {
+            v: &self.cam_to_lcs * &ray.v,
+            o: &self.cam_to_lcs * &ray.o,
+        };
+
+        if nearly_zero(ray.v.y) {
+            return None;
+        }
+        let t = -ray.o.y / ray.v.y;
+        let x = ray.o.x + t * ray.v.x;
+        let z = ray.o.z + t * ray.v.z;
+        if x * x + z * z <= self.radius2 {
+            Some(t)
+        } else {
+            None
+        }
+    }
+}
+
+impl Shapes for Disk {
+    fn set_transform(&mut self, m: Matrix) {
+        self.cam_to_lcs = m;
+    }
+
+    fn get_transform(&self) -> &Matrix {
+        &self.cam_to_lcs
+    }
+
+    fn get_matrix_to_lcs(&self) -> &Matrix {
+        self.cs.get_matrix_to_lcs()
+    }
+
+    fn get_matrix_to_rcs(&self) -> &Matrix {
+        self.cs.get_matrix_to_rcs()
+    }
+
+    fn set_shape_cs(&mut self, cs: Cs) {
+        self.cs = cs;
+    }
+
+    fn intersect(&self, ray: &Ray) -> bool {
+        self.intersect_min(ray).is_some()
+    }
+
+    fn intersect_min(&self, ray: &Ray) -> Option<f64> {
+        self.crossing(ray).filter(|t| *t > 0.)
+    }
+
+    // a disk has no inside: its crossing is a single point
+    fn intervals(&self, ray: &Ray) -> IntervalList {
+        match self.crossing(ray) {
+            Some(t) => IntervalList::from(Interval::singleton(t)),
+            None => IntervalList::new(),
+        }
+    }
+
+    fn local_bounds(&self) -> Aabb {
+        let r = self.radius;
+        Aabb::new(Point::new(-r, 0., -r), Point::new(r, 0., r))
+    }
+
+    fn normal_at(&self, _p: &Point) -> Vector {
+        J
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{nearly_equal, Camera};
+
+    #[test]
+    fn hit_1() {
+        let mut disk = Disk::build(2.);
+        let mut cs = Cs::new();
+        cs.rotate_x(-90.);
+        cs.translate(&Vector::new(0., 0., 5.));
+        disk.set_shape_cs(cs);
+        disk.compute_camcs_to_shapecs(&Camera::new());
+
+        let ray = Ray::new(Point::new(1., 1., 0.), Vector::new(0., 0., 1.));
+        let hit = disk.hit(&ray).unwrap();
+        assert!(nearly_equal(hit.t, 5.));
+        assert!(hit.normal.nearly_equal(&Vector::new(0., 0., -1.)));
+
+        let ray = Ray::new(Point::new(1.5, 1.5, 0.), Vector::new(0., 0., 1.));
+        assert!(!disk.intersect(&ray));
+
+        let b = disk.bounds();
+        assert!(b.min.nearly_equal(&Point::new(-2., -2., 5.)));
+        assert!(b.max.nearly_equal(&Point::new(2., 2., 5.)));
+    }
+}
@@ -0,0 +1,390 @@
+use super::triangle::moller_trumbore;
+use crate::{
+    Aabb, Bound, Bvh, Cs, Hit, Interval, IntervalList, Matrix, Point, Ray, Shapes, Vector,
+};
+
+// Indexed triangle mesh: triangles share the vertex buffer and may carry
+// per-vertex normals (smooth shading) and texture coordinates. Triangles
+// are searched through a BVH built in shape cs.
+pub struct Mesh {
+    pub cs: Cs,
+    pub cam_to_lcs: Matrix,
+    vertices: Vec<Point>,
+    indices: Vec<[usize; 3]>,
+    normals: Option<Vec<Vector>>,
+    uvs: Option<Vec<(f64, f64)>>,
+    bvh: Bvh,
+}
+
+impl Mesh {
+    pub fn build(vertices: Vec<Point>, indices: Vec<[usize; 3]>) -> Mesh {
+        assert!(indices.iter().flatten().all(|i| *i < vertices.len()));
+
+        let bounds: Vec<Aabb> = indices
+            .iter()
+            .map(|tri| {
+                let mut b = Aabb::empty();
+                for i in tri {
+                    b.extend(&vertices[*i]);
+                }
+                b
+            })
+            .collect();
+
+        Mesh {
+            cs: Cs::new(),
+            cam_to_lcs: Matrix::default(),
+            bvh: Bvh::build(&bounds),
+            vertices,
+            indices,
+            normals: None,
+            uvs: None,
+        }
+    }
+
+    // one normal per vertex, in shape cs
+    pub fn with_normals(mut self, normals: Vec<Vector>) -> Mesh {
+        assert_eq!(normals.len(), self.vertices.len());
+        self.normals = Some(normals);
+        self
+    }
+
+    // one (u, v) per vertex
+    pub fn with_uvs(mut self, uvs: Vec<(f64, f64)>) -> Mesh {
+        assert_eq!(uvs.len(), self.vertices.len());
+        self.uvs = Some(uvs);
+        self
+    }
+
+    pub fn vertices(&self) -> &[Point] {
+        &self.vertices
+    }
+
+    pub fn indices(&self) -> &[[usize; 3]] {
+        &self.indices
+    }
+
+    fn local_ray(&self, ray: &Ray) -> Ray {
+        Ray {
+            v: &self.cam_to_lcs * &ray.v,
+            o: &self.cam_to_lcs * &ray.o,
+        }
+    }
+
+    fn triangle_hit(&self, ray: &Ray, tri: usize) -> Option<(f64, f64, f64)> {
+        let [a, b, c] = self.indices[tri];
+        moller_trumbore(ray, &self.vertices[a], &self.vertices[b], &self.vertices[c])
+    }
+
+    // closest triangle ahead of the local ray, with t and barycentric (u, v)
+    fn closest(&self, ray: &Ray) -> Option<(usize, f64, f64, f64)> {
+        let mut best = None;
+        self.bvh.closest(ray, f64::INFINITY, |tri, t_best| {
+            let (t, u, v) = self
+                .triangle_hit(ray, tri)
+                .filter(|(t, _, _)| *t > 0. && *t < t_best)?;
+            best = Some((tri, t, u, v));
+            Some(t)
+        });
+        best
+    }
+
+    // every crossing of the line, sorted; a ray through an edge or a vertex
+    // hits every triangle sharing it: these hits are one crossing
+    fn crossings(&self, ray: &Ray) -> Vec<f64> {
+        let mut ts = vec![];
+        self.bvh.any(ray, f64::NEG_INFINITY, f64::INFINITY, |tri| {
+            if let Some((t, _, _)) = self.triangle_hit(ray, tri) {
+                ts.push(t);
+            }
+            false
+        });
+        ts.sort_by(f64::total_cmp);
+        ts.dedup_by(|t, first| *t - *first <= tolerance(*first));
+        ts
+    }
+
+    // hit record of triangle tri crossed at t, (u, v) barycentric
+    fn triangle_hit_record(
+        &self,
+        ray: &Ray,
+        local: &Ray,
+        tri: usize,
+        t: f64,
+        uv: (f64, f64),
+    ) -> Hit {
+        let [a, b, c] = self.indices[tri];
+        let (u, v) = uv;
+        let w = 1. - u - v;
+        let normal = match &self.normals {
+            Some(n) => w * &n[a] + u * &n[b] + v * &n[c],
+            None => {
+                (&self.vertices[b] - &self.vertices[a]) ^ (&self.vertices[c] - &self.vertices[a])
+            }
+        };
+
+        let point = &ray.o + t * &ray.v;
+        let world_point = self.get_matrix_to_rcs() * &(&local.o + t * &local.v);
+        let normal = self.get_transform().transpose() * normal;
+
+        let mut hit = Hit::new(t, point, world_point, normal, &ray.v);
+        hit.uv = self.uvs.as_ref().map(|uv| {
+            (
+                w * uv[a].0 + u * uv[b].0 + v * uv[c].0,
+                w * uv[a].1 + u * uv[b].1 + v * uv[c].1,
+            )
+        });
+        hit
+    }
+}
+
+impl Shapes for Mesh {
+    fn set_transform(&mut self, m: Matrix) {
+        self.cam_to_lcs = m;
+    }
+
+    fn get_transform(&self) -> &Matrix {
+        &self.cam_to_lcs
+    }
+
+    fn get_matrix_to_lcs(&self) -> &Matrix {
+        self.cs.get_matrix_to_lcs()
+    }
+
+    fn get_matrix_to_rcs(&self) -> &Matrix {
+        self.cs.get_matrix_to_rcs()
+    }
+
+    fn set_shape_cs(&mut self, cs: Cs) {
+        self.cs = cs;
+    }
+
+    fn intersect(&self, ray: &Ray) -> bool {
+        self.intersect_min(ray).is_some()
+    }
+
+    fn intersect_min(&self, ray: &Ray) -> Option<f64> {
+        self.closest(&self.local_ray(ray)).map(|(_, t, _, _)| t)
+    }
+
+    // the closest triangle is kept along with its hit
+    fn hit(&self, ray: &Ray) -> Option<Hit> {
+        let local = self.local_ray(ray);
+        let (tri, t, u, v) = self.closest(&local)?;
+        Some(self.triangle_hit_record(ray, &local, tri, t, (u, v)))
+    }
+
+    // crossings of a closed mesh pair up into entry/exit intervals, an open
+    // mesh (odd count) gives single points like a triangle
+    fn intervals(&self, ray: &Ray) -> IntervalList {
+        let ts = self.crossings(&self.local_ray(ray));
+
+        if ts.len().is_multiple_of(2) {
+            IntervalList::from(
+                ts.chunks(2)
+                    .map(|k| Interval::new(Bound::Closed(k[0]), Bound::Closed(k[1])))
+                    .collect::<Vec<Interval>>(),
+            )
+        } else {
+            IntervalList::from(
+                ts.iter()
+                    .map(|t| Interval::singleton(*t))
+                    .collect::<Vec<Interval>>(),
+            )
+        }
+    }
+
+    fn any_hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> bool {
+        let ray = self.local_ray(ray);
+
+        self.bvh.any(&ray, t_min, t_max, |tri| {
+            self.triangle_hit(&ray, tri)
+                .is_some_and(|(t, _, _)| t >= t_min && t <= t_max)
+        })
+    }
+
+    fn local_bounds(&self) -> Aabb {
+        let mut b = Aabb::empty();
+        for p in &self.vertices {
+            b.extend(p);
+        }
+        b
+    }
+
+    fn normal_at(&self, _p: &Point) -> Vector {
+        unreachable!("mesh normals depend on the triangle, computed in hit_at")
+    }
+
+    // t comes from intervals (CSG): the triangle crossed at t is found back,
+    // crossings are recomputed on the same ray so the nearest one is exact
+    fn hit_at(&self, ray: &Ray, t: f64) -> Option<Hit> {
+        let local = self.local_ray(ray);
+        let tol = tolerance(t);
+
+        let mut found: Option<(usize, f64, f64, f64)> = None;
+        self.bvh.any(&local, t - tol, t + tol, |tri| {
+            if let Some((k, u, v)) = self.triangle_hit(&local, tri) {
+                if found.is_none_or(|(_, best, _, _)| (k - t).abs() < (best - t).abs()) {
+                    found = Some((tri, k, u, v));
+                }
+            }
+            false
+        });
+        let (tri, k, u, v) = found.filter(|(_, k, _, _)| (k - t).abs() <= tol)?;
+
+        Some(self.triangle_hit_record(ray, &local, tri, k, (u, v)))
+    }
+}
+
+// crossings closer than this to t are the same
+fn tolerance(t: f64) -> f64 {
+    1e-9 * t.abs().max(1.)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{nearly_equal, Camera, Csg, CsgOp};
+
+    // unit cube centered on origin, counter-clockwise faces seen from outside
+    fn cube() -> Mesh {
+        let vertices = (0..8)
+            .map(|i| {
+                Point::new(
+                    if i & 1 == 0 { -1. } else { 1. },
+                    if i & 2 == 0 { -1. } else { 1. },
+                    if i & 4 == 0 { -1. } else { 1. },
+                )
+            })
+            .collect();
+        let quads = [
+            [0, 2, 3, 1], // z-
+            [4, 5, 7, 6], // z+
+            [0, 1, 5, 4], // y-
+            [2, 6, 7, 3], // y+
+            [0, 4, 6, 2], // x-
+            [1, 3, 7, 5], // x+
+        ];
+        let indices = quads
+            .iter()
+            .flat_map(|q| [[q[0], q[1], q[2]], [q[0], q[2], q[3]]])
+            .collect();
+        Mesh::build(vertices, indices)
+    }
+
+    #[test]
+    fn cube_1() {
+        let mut mesh = cube();
+        let mut cs = Cs::new();
+        cs.translate(&Vector::new(0., 0., 10.));
+        mesh.set_shape_cs(cs);
+        mesh.compute_camcs_to_shapecs(&Camera::new());
+
+        let ray = Ray::new(Point::new(0.2, 0.3, 0.), Vector::new(0., 0., 1.));
+        let hit = mesh.hit(&ray).unwrap();
+        assert!(nearly_equal(hit.t, 9.));
+        assert!(hit.normal.nearly_equal(&Vector::new(0., 0., -1.)));
+        assert!(hit.front_face);
+        assert!(hit.world_point.nearly_equal(&Point::new(0.2, 0.3, 9.)));
+        assert!(hit.uv.is_none());
+
+        let list = mesh.intervals(&ray);
+        assert_eq!(list.len(), 1);
+        assert!(nearly_equal(list.as_slice()[0].sup().unwrap(), 11.));
+
+        assert!(mesh.any_hit(&ray, 0., 9.5));
+        assert!(!mesh.any_hit(&ray, 0., 8.5));
+        assert!(!mesh.intersect(&Ray::new(Point::new(2., 0., 0.), Vector::new(0., 0., 1.))));
+    }
+
+    #[test]
+    fn cube_diagonal() {
+        let mut mesh = cube();
+        let mut cs = Cs::new();
+        cs.translate(&Vector::new(0., 0., 10.));
+        mesh.set_shape_cs(cs);
+        mesh.compute_camcs_to_shapecs(&Camera::new());
+
+        // along the diagonals shared by the two triangles of the z faces
+        for (x, y) in [(0.5, 0.5), (0., 0.), (-0.3, -0.3)] {
+            let ray = Ray::new(Point::new(x, y, 0.), Vector::new(0., 0., 1.));
+            let list = mesh.intervals(&ray);
+            assert_eq!(list.len(), 1);
+            assert!(nearly_equal(list.as_slice()[0].inf().unwrap(), 9.));
+            assert!(nearly_equal(list.as_slice()[0].sup().unwrap(), 11.));
+        }
+
+        // in through the center of the x- face, out through the y- one, both
+        // on a diagonal
+        let ray = Ray::new(Point::new(-10., 9., 10.), Vector::new(1., -1., 0.).unit());
+        let list = mesh.intervals(&ray);
+        assert_eq!(list.len(), 1);
+        let (inf, sup) = (list.as_slice()[0].inf(), list.as_slice()[0].sup());
+        assert!(nearly_equal(inf.unwrap(), 9. * 2f64.sqrt()));
+        assert!(nearly_equal(sup.unwrap(), 10. * 2f64.sqrt()));
+    }
+
+    #[test]
+    fn smooth_and_uv() {
+        let vertices = vec![
+            Point::new(0., 0., 1.),
+            Point::new(1., 0., 1.),
+            Point::new(0., 1., 1.),
+        ];
+        let normals = vec![
+            Vector::new(0., 0., -1.),
+            Vector::new(-1., 0., -1.).unit(),
+            Vector::new(0., 0., -1.),
+        ];
+        let mut mesh = Mesh::build(vertices, vec![[0, 1, 2]])
+            .with_normals(normals)
+            .with_uvs(vec![(0., 0.), (1., 0.), (0., 1.)]);
+        mesh.compute_camcs_to_shapecs(&Camera::new());
+
+        let ray = Ray::new(Point::new(0.5, 0.25, 0.), Vector::new(0., 0., 1.));
+        let hit = mesh.hit(&ray).unwrap();
+        let (u, v) = hit.uv.unwrap();
+        assert!(nearly_equal(u, 0.5));
+        assert!(nearly_equal(v, 0.25));
+        let expected =
+            (0.5 * Vector::new(0., 0., -1.) + 0.5 * Vector::new(-1., 0., -1.).unit()).unit();
+        assert!(hit.normal.nearly_equal(&expected));
+    }
+
+    #[test]
+    fn csg() {
+        let mut a = cube();
+        let mut cs = Cs::new();
+        cs.translate(&Vector::new(0., 0., 10.));
+        a.set_shape_cs(cs);
+        let mut b = cube();
+        let mut cs = Cs::new();
+        cs.translate(&Vector::new(0., 0., 11.));
+        b.set_shape_cs(cs);
+
+        let mut csg = Csg::build(CsgOp::Difference, Box::new(a), Box::new(b));
+        csg.compute_camcs_to_shapecs(&Camera::new());
+
+        let ray = Ray::new(Point::new(0.2, 0.3, 0.), Vector::new(0., 0., 1.));
+        let list = csg.intervals(&ray);
+        assert_eq!(list.len(), 1);
+        assert!(nearly_equal(list.as_slice()[0].sup().unwrap(), 10.));
+
+        // the faces are found back from the interval bounds
+        let hit = csg.hit(&ray).unwrap();
+        assert!(nearly_equal(hit.t, 9.));
+        assert!(hit.normal.nearly_equal(&Vector::new(0., 0., -1.)));
+    }
+
+    #[test]
+    fn hit_at() {
+        let mut mesh = cube();
+        mesh.compute_camcs_to_shapecs(&Camera::new());
+        let ray = Ray::new(Point::new(0.2, 0.3, -5.), Vector::new(0., 0., 1.));
+
+        let hit = mesh.hit_at(&ray, 6.).unwrap();
+        assert!(hit.point.nearly_equal(&Point::new(0.2, 0.3, 1.)));
+        assert!(!hit.front_face);
+        assert!(mesh.hit_at(&ray, 5.).is_none());
+    }
+}
@@ -0,0 +1,127 @@
+use super::Shapes;
+use crate::{nearly_zero, Aabb, Bound, Cs, Interval, IntervalList, Matrix, Point, Ray, Vector, J};
+
+// Plane y = 0 of its cs, normal +y. As a CSG solid it is the half-space
+// y <= 0.
+pub struct Plane {
+    pub cs: Cs,
+    pub cam_to_lcs: Matrix,
+}
+
+impl Plane {
+    pub fn build() -> Plane {
+        Plane {
+            cs: Cs::new(),
+            cam_to_lcs: Matrix::default(),
+        }
+    }
+}
+
+impl Shapes for Plane {
+    fn set_transform(&mut self, m: Matrix) {
+        self.cam_to_lcs = m;
+    }
+
+    fn get_transform(&self) -> &Matrix {
+        &self.cam_to_lcs
+    }
+
+    fn get_matrix_to_lcs(&self) -> &Matrix {
+        self.cs.get_matrix_to_lcs()
+    }
+
+    fn get_matrix_to_rcs(&self) -> &Matrix {
+        self.cs.get_matrix_to_rcs()
+    }
+
+    fn set_shape_cs(&mut self, cs: Cs) {
+        self.cs = cs;
+    }
+
+    fn intersect(&self, ray: &Ray) -> bool {
+        self.intersect_min(ray).is_some()
+    }
+
+    fn intersect_min(&self, ray: &Ray) -> Option<f64> {
+        let ray = Ray {
+            v: &self.cam_to_lcs * &ray.v,
+            o: &self.cam_to_lcs * &ray.o,
+        };
+
+        if nearly_zero(ray.v.y) {
+            None
+        } else {
+            Some(-ray.o.y / ray.v.y).filter(|t| *t > 0.)
+        }
+    }
+
+    fn intervals(&self, ray: &Ray) -> IntervalList {
+        let ray = Ray {
+            v: &self.cam_to_lcs * &ray.v,
+            o: &self.cam_to_lcs * &ray.o,
+        };
+
+        if nearly_zero(ray.v.y) {
+            // parallel to the plane: below all along, or never
+            if ray.o.y <= 0. {
+                IntervalList::from(Interval::new(
+                    Bound::NegativeInfinity,
+                    Bound::PositiveInfinity,
+                ))
+            } else {
+                IntervalList::new()
+            }
+        } else {
+            let t = -ray.o.y / ray.v.y;
+            if ray.v.y > 0. {
+                IntervalList::from(Interval::new(Bound::NegativeInfinity, Bound::Closed(t)))
+            } else {
+                IntervalList::from(Interval::new(Bound::Closed(t), Bound::PositiveInfinity))
+            }
+        }
+    }
+
+    fn local_bounds(&self) -> Aabb {
+        Aabb::new(
+            Point::new(f64::NEG_INFINITY, f64::NEG_INFINITY, f64::NEG_INFINITY),
+            Point::new(f64::INFINITY, 0., f64::INFINITY),
+        )
+    }
+
+    fn normal_at(&self, _p: &Point) -> Vector {
+        J
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{nearly_equal, Camera};
+
+    #[test]
+    fn ground() {
+        let mut plane = Plane::build();
+        let mut cs = Cs::new();
+        cs.translate(&Vector::new(0., -1., 0.));
+        plane.set_shape_cs(cs);
+        plane.compute_camcs_to_shapecs(&Camera::new());
+
+        let ray = Ray::new(Point::new(0., 0., 0.), Vector::new(0., -1., 1.));
+        let hit = plane.hit(&ray).unwrap();
+        assert!(nearly_equal(hit.t, 1.));
+        assert!(hit.world_point.nearly_equal(&Point::new(0., -1., 1.)));
+        assert!(hit.normal.nearly_equal(&J));
+        assert!(hit.front_face);
+
+        // the ray goes into the half-space and stays there
+        let list = plane.intervals(&ray);
+        assert!(list.contains(100.));
+        assert!(!list.contains(0.5));
+
+        let ray = Ray::new(Point::new(0., 0., 0.), Vector::new(0., 1., 1.));
+        assert!(!plane.intersect(&ray));
+        let ray = Ray::new(Point::new(0., 0., 0.), Vector::new(0., 0., 1.));
+        assert!(!plane.intersect(&ray));
+        assert!(plane.intervals(&ray).is_empty());
+    }
+}
@@ -0,0 +1,158 @@
+use super::{first_bound, Shapes};
+use crate::{solve_quartic, Aabb, Bound, Cs, Interval, IntervalList, Matrix, Point, Ray, Vector};
+
+// Torus around the y axis: circle of radius minor swept along the circle of
+// radius major in the plane y = 0.
+pub struct Torus {
+    pub cs: Cs,
+    pub cam_to_lcs: Matrix,
+    pub major: f64,
+    pub minor: f64,
+}
+
+impl Torus {
+    pub fn build(major: f64, minor: f64) -> Torus {
+        assert!(minor > 0. && major > minor);
+
+        Torus {
+            cs: Cs::new(),
+            cam_to_lcs: Matrix::default(),
+            major,
+            minor,
+        }
+    }
+}
+
+impl Shapes for Torus {
+    fn set_transform(&mut self, m: Matrix) {
+        self.cam_to_lcs = m;
+    }
+
+    fn get_transform(&self) -> &Matrix {
+        &self.cam_to_lcs
+    }
+
+    fn get_matrix_to_lcs(&self) -> &Matrix {
+        self.cs.get_matrix_to_lcs()
+    }
+
+    fn get_matrix_to_rcs(&self) -> &Matrix {
+        self.cs.get_matrix_to_rcs()
+    }
+
+    fn set_shape_cs(&mut self, cs: Cs) {
+        self.cs = cs;
+    }
+
+    fn intersect(&self, ray: &Ray) -> bool {
+        self.intersect_min(ray).is_some()
+    }
+
+    fn intersect_min(&self, ray: &Ray) -> Option<f64> {
+        first_bound(&self.intervals(ray))
+    }
+
+    fn intervals(&self, ray: &Ray) -> IntervalList {
+        let ray = Ray {
+            v: &self.cam_to_lcs * &ray.v,
+            o: &self.cam_to_lcs * &ray.o,
+        };
+
+        // the quartic is solved from the bounding box entry: far origins
+        // would otherwise swamp the coefficients
+        let Some((t0, _)) = self
+            .local_bounds()
+            .intersect(&ray, f64::NEG_INFINITY, f64::INFINITY)
+        else {
+            return IntervalList::new();
+        };
+        let o = &ray.o + t0 * &ray.v;
+        let v = &ray.v;
+
+        // (|p|² + R² - r²)² = 4R²(x² + z²)
+        let r2 = self.major * self.major;
+        let a = v * v;
+        let b = 2. * (o.x * v.x + o.y * v.y + o.z * v.z);
+        let c = o.x * o.x + o.y * o.y + o.z * o.z + r2 - self.minor * self.minor;
+        let roots = solve_quartic(
+            a * a,
+            2. * a * b,
+            b * b + 2. * a * c - 4. * r2 * (v.x * v.x + v.z * v.z),
+            2. * b * c - 8. * r2 * (o.x * v.x + o.z * v.z),
+            c * c - 4. * r2 * (o.x * o.x + o.z * o.z),
+        );
+
+        // entry/exit pairs, a tangent ray gives a double root: the root count
+        // of the quartic is even
+        assert!(roots.len().is_multiple_of(2));
+        let intervals: Vec<Interval> = roots
+            .chunks_exact(2)
+            .map(|k| Interval::new(Bound::Closed(t0 + k[0]), Bound::Closed(t0 + k[1])))
+            .collect();
+        IntervalList::from(intervals)
+    }
+
+    fn local_bounds(&self) -> Aabb {
+        let (r, h) = (self.major + self.minor, self.minor);
+        Aabb::new(Point::new(-r, -h, -r), Point::new(r, h, r))
+    }
+
+    // from the center of the tube section through p
+    fn normal_at(&self, p: &Point) -> Vector {
+        let k = self.major / (p.x * p.x + p.z * p.z).sqrt();
+        Vector::new(p.x - k * p.x, p.y, p.z - k * p.z)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{nearly_equal, Camera, J, K};
+
+    #[test]
+    fn hit_1() {
+        let mut torus = Torus::build(2., 0.5);
+        let mut cs = Cs::new();
+        cs.rotate_x(-90.);
+        cs.translate(&Vector::new(0., 0., 10.));
+        torus.set_shape_cs(cs);
+        torus.compute_camcs_to_shapecs(&Camera::new());
+
+        // facing the camera: the ray goes through the tube along z
+        let ray = Ray::new(Point::new(2., 0., 0.), K);
+        let hit = torus.hit(&ray).unwrap();
+        assert!(nearly_equal(hit.t, 9.5));
+        assert!(hit.normal.nearly_equal(&-K));
+        let list = torus.intervals(&ray);
+        assert_eq!(list.len(), 1);
+        assert!(nearly_equal(list.as_slice()[0].sup().unwrap(), 10.5));
+
+        // through the hole
+        let ray = Ray::new(Point::new(0., 0., 0.), K);
+        assert!(!torus.intersect(&ray));
+    }
+
+    #[test]
+    fn across() {
+        let mut torus = Torus::build(2., 0.5);
+        torus.compute_camcs_to_shapecs(&Camera::new());
+
+        // along x in the torus plane, from far away: two tube sections
+        let ray = Ray::new(Point::new(-1000., 0., 0.), Vector::new(1., 0., 0.));
+        let list = torus.intervals(&ray);
+        assert_eq!(list.len(), 2);
+        let bounds: Vec<f64> = list
+            .iter()
+            .flat_map(|i| [i.inf().unwrap(), i.sup().unwrap()])
+            .collect();
+        for (k, e) in bounds.iter().zip([997.5, 998.5, 1001.5, 1002.5]) {
+            assert!(nearly_equal(*k, e));
+        }
+
+        // outer equator normal
+        let hit = torus.hit(&ray).unwrap();
+        assert!(hit.normal.nearly_equal(&Vector::new(-1., 0., 0.)));
+        let ray = Ray::new(Point::new(2., 5., 0.), -J);
+        assert!(torus.hit(&ray).unwrap().normal.nearly_equal(&J));
+    }
+}
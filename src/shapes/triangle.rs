@@ -0,0 +1,197 @@
+use super::PARALLEL_TH;
+use crate::{Aabb, Cs, Interval, IntervalList, Matrix, Point, Ray, Shapes, Vector};
+
+pub struct Triangle {
+    pub cs: Cs,
+    pub cam_to_lcs: Matrix,
+    pub a: Point,
+    pub b: Point,
+    pub c: Point,
+}
+
+impl Triangle {
+    // vertices in shape cs, counter-clockwise around the outward normal.
+    // Fails if the vertices are aligned.
+    pub fn build(a: Point, b: Point, c: Point) -> Result<Triangle, &'static str> {
+        if Triangle::is_degenerate(&a, &b, &c) {
+            return Err("degenerate triangle");
+        }
+
+        Ok(Triangle {
+            cs: Cs::new(),
+            cam_to_lcs: Matrix::default(),
+            a,
+            b,
+            c,
+        })
+    }
+
+    pub fn is_degenerate(a: &Point, b: &Point, c: &Point) -> bool {
+        let (e1, e2) = (b - a, c - a);
+        (&e1 ^ &e2).length() <= PARALLEL_TH * e1.length() * e2.length()
+    }
+}
+
+// Möller–Trumbore: ray parameter and barycentric coordinates (u, v) of the
+// crossing point, for every t along the line
+pub(super) fn moller_trumbore(
+    ray: &Ray,
+    a: &Point,
+    b: &Point,
+    c: &Point,
+) -> Option<(f64, f64, f64)> {
+    let e1 = b - a;
+    let e2 = c - a;
+    let p = &ray.v ^ &e2;
+    let det = &e1 * &p;
+    if det.abs() <= PARALLEL_TH * e1.length() * e2.length() * ray.v.length() {
+        return None;
+    }
+
+    let inv = 1. / det;
+    let s = &ray.o - a;
+    let u = inv * (&s * &p);
+    if !(0. ..=1.).contains(&u) {
+        return None;
+    }
+
+    let q = &s ^ &e1;
+    let v = inv * (&ray.v * &q);
+    if v < 0. || u + v > 1. {
+        return None;
+    }
+
+    Some((inv * (&e2 * &q), u, v))
+}
+
+impl Shapes for Triangle {
+    fn set_transform(&mut self, m: Matrix) {
+        self.cam_to_lcs = m;
+    }
+
+    fn get_transform(&self) -> &Matrix {
+        &self.cam_to_lcs
+    }
+
+    fn get_matrix_to_lcs(&self) -> &Matrix {
+        self.cs.get_matrix_to_lcs()
+    }
+
+    fn get_matrix_to_rcs(&self) -> &Matrix {
+        self.cs.get_matrix_to_rcs()
+    }
+
+    fn set_shape_cs(&mut self, cs: Cs) {
+        self.cs = cs;
+    }
+
+    fn intersect(&self, ray: &Ray) -> bool {
+        self.intersect_min(ray).is_some()
+    }
+
+    fn intersect_min(&self, ray: &Ray) -> Option<f64> {
+        let ray = Ray {
+            v: &self.cam_to_lcs * &ray.v,
+            o: &self.cam_to_lcs * &ray.o,
+        };
+
+        moller_trumbore(&ray, &self.a, &self.b, &self.c)
+            .map(|(t, _, _)| t)
+            .filter(|t| *t > 0.)
+    }
+
+    // a triangle has no inside: its crossing is a single point
+    fn intervals(&self, ray: &Ray) -> IntervalList {
+        let ray = Ray {
+            v: &self.cam_to_lcs * &ray.v,
+            o: &self.cam_to_lcs * &ray.o,
+        };
+
+        match moller_trumbore(&ray, &self.a, &self.b, &self.c) {
+            Some((t, _, _)) => IntervalList::from(Interval::singleton(t)),
+            None => IntervalList::new(),
+        }
+    }
+
+    fn local_bounds(&self) -> Aabb {
+        let mut b = Aabb::empty();
+        for p in [&self.a, &self.b, &self.c] {
+            b.extend(p);
+        }
+        b
+    }
+
+    fn normal_at(&self, _p: &Point) -> Vector {
+        (&self.b - &self.a) ^ (&self.c - &self.a)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{nearly_equal, Camera};
+
+    #[test]
+    fn hit_1() {
+        let mut tri = Triangle::build(
+            Point::new(-1., -1., 0.),
+            Point::new(-1., 1., 0.),
+            Point::new(1., -1., 0.),
+        )
+        .unwrap();
+        let mut cs = Cs::new();
+        cs.translate(&Vector::new(0., 0., 5.));
+        tri.set_shape_cs(cs);
+        tri.compute_camcs_to_shapecs(&Camera::new());
+
+        let ray = Ray::new(Point::new(-0.5, -0.5, 0.), Vector::new(0., 0., 1.));
+        let hit = tri.hit(&ray).unwrap();
+        assert!(nearly_equal(hit.t, 5.));
+        assert!(hit.normal.nearly_equal(&Vector::new(0., 0., -1.)));
+        assert!(hit.front_face);
+
+        // outside the hypotenuse, then behind the origin
+        let ray = Ray::new(Point::new(0.5, 0.5, 0.), Vector::new(0., 0., 1.));
+        assert!(!tri.intersect(&ray));
+        let ray = Ray::new(Point::new(-0.5, -0.5, 10.), Vector::new(0., 0., 1.));
+        assert!(!tri.intersect(&ray));
+        assert_eq!(tri.intervals(&ray).len(), 1);
+    }
+
+    #[test]
+    fn barycentric() {
+        let (a, b, c) = (
+            Point::new(0., 0., 0.),
+            Point::new(1., 0., 0.),
+            Point::new(0., 1., 0.),
+        );
+        let ray = Ray::new(Point::new(0.25, 0.5, -1.), Vector::new(0., 0., 2.));
+        let (t, u, v) = moller_trumbore(&ray, &a, &b, &c).unwrap();
+        assert!(nearly_equal(t, 0.5));
+        assert!(nearly_equal(u, 0.25));
+        assert!(nearly_equal(v, 0.5));
+
+        // parallel to the triangle plane
+        let ray = Ray::new(Point::new(0.25, 0.5, -1.), Vector::new(1., 0., 0.));
+        assert!(moller_trumbore(&ray, &a, &b, &c).is_none());
+    }
+
+    #[test]
+    fn tiny() {
+        // sides of 1e-4, whatever the mesh unit
+        let k = 1e-4;
+        let (a, b, c) = (
+            Point::new(0., 0., 1.),
+            Point::new(k, 0., 1.),
+            Point::new(0., k, 1.),
+        );
+        let ray = Ray::new(Point::new(k / 4., k / 4., 0.), Vector::new(0., 0., 1.));
+        let (t, u, v) = moller_trumbore(&ray, &a, &b, &c).unwrap();
+        assert!(nearly_equal(t, 1.));
+        assert!(nearly_equal(u, 0.25) && nearly_equal(v, 0.25));
+        assert!(Triangle::build(a, b, c).is_ok());
+
+        let (a, b) = (Point::new(0., 0., 0.), Point::new(1., 1., 1.));
+        assert!(Triangle::build(a, b, Point::new(2., 2., 2.)).is_err());
+    }
+}